@@ -3,3 +3,13 @@
 #![allow(non_snake_case)]
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+#[cfg(feature = "jsc-private")]
+extern "C" {
+    /// Exported by JavaScriptCore, but declared in `JSBasePrivate.h` which the SDK doesn't ship.
+    ///
+    /// Returns an object with `heapSize`, `heapCapacity`, `extraMemorySize`, `objectCount`,
+    /// `protectedObjectCount`, `globalObjectCount`, `protectedGlobalObjectCount` and
+    /// `objectTypeCounts`.
+    pub fn JSGetMemoryUsageStatistics(ctx: JSContextRef) -> JSObjectRef;
+}
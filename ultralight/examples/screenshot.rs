@@ -1,7 +1,6 @@
 use std::{
     fs::File,
-    io::Read,
-    path::{Path, PathBuf},
+    path::Path,
     rc::Rc,
     sync::mpsc::{channel, Receiver},
     thread::sleep,
@@ -10,11 +9,11 @@ use std::{
 
 use png::{ColorType, Encoder};
 use ultralight::{
-    buffer::Buffer,
     config::{Config, ViewConfig},
-    filesystem::{set_platform_file_system, FileSystem},
-    logger::{set_platform_logger, LogLevel},
-    platform::enable_platform_font_loader,
+    filesystem::DirFileSystem,
+    logger::LogLevel,
+    platform::Platform,
+    render_loop::RenderLoop,
     renderer::Renderer,
     surface::{BitmapSurface, Surface},
     view::View,
@@ -23,40 +22,6 @@ use ultralight::{
 const UL_HTML: &str = include_str!("./ultralight.html");
 const EXAMPLE_HTML: &str = include_str!("./example.html");
 
-struct MyFileSystem {
-    base_path: PathBuf,
-}
-
-impl MyFileSystem {
-    fn new(base_path: PathBuf) -> Self {
-        Self { base_path }
-    }
-}
-
-impl FileSystem for MyFileSystem {
-    fn file_exists(&self, path: &Path) -> bool {
-        self.base_path.join(path).exists()
-    }
-
-    fn get_file_mime_type(&self, _path: &Path) -> String {
-        "application/unknown".into()
-    }
-
-    fn get_file_charset(&self, _path: &Path) -> String {
-        "utf-8".into()
-    }
-
-    fn open_file(&self, path: &Path) -> Buffer {
-        let path = self.base_path.join(path);
-
-        let mut data = Vec::with_capacity(path.metadata().unwrap().len() as usize);
-        let mut file = File::open(&path).unwrap();
-        file.read_to_end(&mut data).unwrap();
-
-        Buffer::new_owned(data)
-    }
-}
-
 fn create_config() -> Config {
     let mut config = Config::default();
     config.set_user_stylesheet("html, body {overflow: hidden}");
@@ -69,8 +34,17 @@ fn create_view_config() -> ViewConfig {
     view_config
 }
 
-fn create_renderer(config: &Config) -> Rc<Renderer> {
-    Renderer::new(config)
+fn create_renderer(config: Config) -> Rc<Renderer> {
+    Platform::builder()
+        .logger(|level, msg| {
+            if let LogLevel::Error = level {
+                println!("{level:?} {msg}")
+            };
+        })
+        .file_system(Box::new(DirFileSystem::new("./").unwrap()))
+        .config(config)
+        .build()
+        .unwrap()
 }
 
 fn create_view(renderer: Rc<Renderer>, width: u32, height: u32, view_config: &ViewConfig) -> View {
@@ -80,7 +54,7 @@ fn create_view(renderer: Rc<Renderer>, width: u32, height: u32, view_config: &Vi
 }
 
 fn _write_png_with_ultralight(surface: &BitmapSurface, path: &Path) {
-    surface.write_png(path);
+    surface.write_png(path).unwrap();
 }
 
 fn write_png_with_png_rs(surface: &mut BitmapSurface, path: &Path) {
@@ -113,14 +87,7 @@ fn do_screenshot(
 
     // Load page
     view.load_html(html);
-    loop {
-        renderer.update();
-        renderer.render();
-
-        if rx.try_recv().is_ok() {
-            break;
-        }
-    }
+    RenderLoop::new(renderer).run_while(|_| rx.try_recv().is_err());
 
     println!("Render cost: {:?}", t.elapsed());
 
@@ -147,19 +114,8 @@ fn do_screenshot(
 }
 
 fn main() {
-    // Basic setup
-    set_platform_logger(|level, msg| {
-        if let LogLevel::Error = level {
-            println!("{level:?} {msg}")
-        };
-    });
-    // equals to ulPlatformSetFileSystem
-    set_platform_file_system(Box::new(MyFileSystem::new("./".into())));
-    enable_platform_font_loader();
-
-    // Make configs
-    let config = create_config();
-    let renderer = create_renderer(&config);
+    // Make configs, register the platform handlers and create the renderer
+    let renderer = create_renderer(create_config());
     let view_config = create_view_config();
 
     // Loaded callback
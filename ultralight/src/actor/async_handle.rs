@@ -0,0 +1,105 @@
+use crate::{
+    actor::{
+        reply::{self, ReplyFuture},
+        ActorError, Command, RendererHandle, Reply, ViewId,
+    },
+    config::ViewConfig,
+    image::RgbaImage,
+};
+
+/// Async version of [`RendererHandle`].
+///
+/// Futures resolve when the renderer thread fires the matching callback, the renderer is pumped
+/// by that thread so nothing needs to spin on the caller side.
+#[derive(Clone)]
+pub struct AsyncRendererHandle {
+    handle: RendererHandle,
+}
+
+impl AsyncRendererHandle {
+    pub async fn create_view(
+        &self,
+        width: u32,
+        height: u32,
+        config: ViewConfig,
+    ) -> Result<ViewId, ActorError> {
+        self.call(|reply| Command::CreateView {
+            width,
+            height,
+            config,
+            reply,
+        })?
+        .await?
+    }
+
+    pub fn destroy_view(&self, view: ViewId) -> Result<(), ActorError> {
+        self.handle.destroy_view(view)
+    }
+
+    /// Resolves when the main frame finished (or failed) loading.
+    pub async fn load_html(&self, view: ViewId, html: &str) -> Result<(), ActorError> {
+        let html = html.to_owned();
+        self.call(|reply| Command::LoadHtml { view, html, reply })?
+            .await?
+    }
+
+    /// Resolves when the main frame finished (or failed) loading.
+    pub async fn load_url(&self, view: ViewId, url: &str) -> Result<(), ActorError> {
+        let url = url.to_owned();
+        self.call(|reply| Command::LoadUrl { view, url, reply })?
+            .await?
+    }
+
+    pub async fn resize(&self, view: ViewId, width: u32, height: u32) -> Result<(), ActorError> {
+        self.call(|reply| Command::Resize {
+            view,
+            width,
+            height,
+            reply,
+        })?
+        .await?
+    }
+
+    /// See [`RendererHandle::evaluate`].
+    pub async fn evaluate(
+        &self,
+        view: ViewId,
+        script: &str,
+    ) -> Result<Result<String, String>, ActorError> {
+        let script = script.to_owned();
+        self.call(|reply| Command::Evaluate {
+            view,
+            script,
+            reply,
+        })?
+        .await?
+    }
+
+    /// Render and copy the surface of the view.
+    pub async fn capture(&self, view: ViewId) -> Result<RgbaImage, ActorError> {
+        self.call(|reply| Command::Capture { view, reply })?.await?
+    }
+}
+
+impl AsyncRendererHandle {
+    pub(crate) fn new(handle: RendererHandle) -> Self {
+        Self { handle }
+    }
+
+    fn call<T: Send + 'static>(
+        &self,
+        command: impl FnOnce(Reply<T>) -> Command,
+    ) -> Result<ReplyFuture<T>, ActorError> {
+        let (sender, future) = reply::channel();
+        self.handle
+            .send(command(Box::new(move |value| sender.send(value))))?;
+
+        Ok(future)
+    }
+}
+
+impl From<RendererHandle> for AsyncRendererHandle {
+    fn from(handle: RendererHandle) -> Self {
+        Self::new(handle)
+    }
+}
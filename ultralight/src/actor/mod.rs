@@ -0,0 +1,490 @@
+//! Run the [`Renderer`] on a dedicated thread.
+//!
+//! [`Renderer`], [`Session`](crate::session::Session) and [`View`] are bound to the thread that
+//! created them. [`RendererThread`] owns them on a background thread and hands out
+//! [`RendererHandle`]s, which are [`Send`] + [`Sync`] and can be cloned freely, to talk to it.
+//! [`AsyncRendererHandle`] offers the same commands as futures.
+
+mod async_handle;
+mod reply;
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    rc::Rc,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+pub use async_handle::AsyncRendererHandle;
+pub use reply::ReplyFuture;
+use ultralight_sys::ULView;
+
+use crate::{
+    config::{Config, ViewConfig},
+    error,
+    image::RgbaImage,
+    renderer::Renderer,
+    view::View,
+    AsULRawPtr,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActorError {
+    /// The renderer thread has exited.
+    Disconnected,
+    UnknownView(ViewId),
+    /// The load was superseded by another load or the view was destroyed.
+    Cancelled,
+    LoadFailed {
+        url: String,
+        description: String,
+        error_code: i32,
+    },
+    Ultralight(error::Error),
+    /// No reply from the renderer thread in time, the command may still complete later.
+    Timeout,
+}
+
+impl Display for ActorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Disconnected => write!(f, "renderer thread has exited"),
+            Self::UnknownView(id) => write!(f, "no view with id {}", id.0),
+            Self::Cancelled => write!(f, "load was cancelled"),
+            Self::LoadFailed {
+                url,
+                description,
+                error_code,
+            } => write!(f, "failed to load {url}: {description} ({error_code})"),
+            Self::Ultralight(e) => e.fmt(f),
+            Self::Timeout => write!(f, "renderer thread did not reply in time"),
+        }
+    }
+}
+
+impl Error for ActorError {}
+
+impl From<error::Error> for ActorError {
+    fn from(e: error::Error) -> Self {
+        Self::Ultralight(e)
+    }
+}
+
+/// How long [`RendererHandle::load_html`] and [`RendererHandle::load_url`] wait for the load.
+pub const DEFAULT_LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ViewId(u64);
+
+pub(crate) type Reply<T> = Box<dyn FnOnce(T) + Send>;
+
+pub(crate) enum Command {
+    CreateView {
+        width: u32,
+        height: u32,
+        config: ViewConfig,
+        reply: Reply<Result<ViewId, ActorError>>,
+    },
+    DestroyView {
+        view: ViewId,
+    },
+    LoadHtml {
+        view: ViewId,
+        html: String,
+        reply: Reply<Result<(), ActorError>>,
+    },
+    LoadUrl {
+        view: ViewId,
+        url: String,
+        reply: Reply<Result<(), ActorError>>,
+    },
+    Resize {
+        view: ViewId,
+        width: u32,
+        height: u32,
+        reply: Reply<Result<(), ActorError>>,
+    },
+    Evaluate {
+        view: ViewId,
+        script: String,
+        reply: Reply<Result<Result<String, String>, ActorError>>,
+    },
+    Capture {
+        view: ViewId,
+        reply: Reply<Result<RgbaImage, ActorError>>,
+    },
+    Shutdown,
+}
+
+/// Owner of the background thread, shuts the renderer down when dropped.
+pub struct RendererThread {
+    handle: RendererHandle,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RendererThread {
+    /// Start the renderer thread, platform setup (logger, file system, font loader) must be done
+    /// before.
+    ///
+    /// `tick` is how often [`Renderer::update`] runs while the thread is otherwise idle.
+    pub fn spawn(config: Config, tick: Duration) -> Result<Self, ActorError> {
+        let (tx, rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let thread = thread::Builder::new()
+            .name("ultralight-renderer".into())
+            .spawn(move || {
+                let renderer = match Renderer::try_new(&config) {
+                    Ok(renderer) => renderer,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = ready_tx.send(Ok(()));
+                run(&renderer, &rx, tick);
+            })
+            .map_err(|_| ActorError::Disconnected)?;
+
+        ready_rx.recv().map_err(|_| ActorError::Disconnected)??;
+
+        Ok(Self {
+            handle: RendererHandle { tx },
+            thread: Some(thread),
+        })
+    }
+
+    pub fn handle(&self) -> RendererHandle {
+        self.handle.clone()
+    }
+
+    pub fn async_handle(&self) -> AsyncRendererHandle {
+        AsyncRendererHandle::new(self.handle.clone())
+    }
+}
+
+impl Drop for RendererThread {
+    fn drop(&mut self) {
+        let _ = self.handle.tx.send(Command::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RendererHandle {
+    tx: Sender<Command>,
+}
+
+impl RendererHandle {
+    pub fn create_view(
+        &self,
+        width: u32,
+        height: u32,
+        config: ViewConfig,
+    ) -> Result<ViewId, ActorError> {
+        self.call(|reply| Command::CreateView {
+            width,
+            height,
+            config,
+            reply,
+        })?
+    }
+
+    pub fn destroy_view(&self, view: ViewId) -> Result<(), ActorError> {
+        self.send(Command::DestroyView { view })
+    }
+
+    /// Load HTML and block until the main frame finished (or failed) loading, at most
+    /// [`DEFAULT_LOAD_TIMEOUT`].
+    pub fn load_html(&self, view: ViewId, html: &str) -> Result<(), ActorError> {
+        self.load_html_timeout(view, html, DEFAULT_LOAD_TIMEOUT)
+    }
+
+    /// Like [`load_html`](Self::load_html), [`ActorError::Timeout`] if the load takes longer than
+    /// `timeout`. The view keeps loading then.
+    pub fn load_html_timeout(
+        &self,
+        view: ViewId,
+        html: &str,
+        timeout: Duration,
+    ) -> Result<(), ActorError> {
+        let html = html.to_owned();
+        self.call_timeout(|reply| Command::LoadHtml { view, html, reply }, timeout)?
+    }
+
+    /// Load a URL and block until the main frame finished (or failed) loading, at most
+    /// [`DEFAULT_LOAD_TIMEOUT`].
+    pub fn load_url(&self, view: ViewId, url: &str) -> Result<(), ActorError> {
+        self.load_url_timeout(view, url, DEFAULT_LOAD_TIMEOUT)
+    }
+
+    /// Like [`load_url`](Self::load_url), [`ActorError::Timeout`] if the load takes longer than
+    /// `timeout`. The view keeps loading then.
+    pub fn load_url_timeout(
+        &self,
+        view: ViewId,
+        url: &str,
+        timeout: Duration,
+    ) -> Result<(), ActorError> {
+        let url = url.to_owned();
+        self.call_timeout(|reply| Command::LoadUrl { view, url, reply }, timeout)?
+    }
+
+    pub fn resize(&self, view: ViewId, width: u32, height: u32) -> Result<(), ActorError> {
+        self.call(|reply| Command::Resize {
+            view,
+            width,
+            height,
+            reply,
+        })?
+    }
+
+    /// See [`View::evaluate_script`], the inner result is the script result or exception.
+    pub fn evaluate(
+        &self,
+        view: ViewId,
+        script: &str,
+    ) -> Result<Result<String, String>, ActorError> {
+        let script = script.to_owned();
+        self.call(|reply| Command::Evaluate {
+            view,
+            script,
+            reply,
+        })?
+    }
+
+    /// Render and copy the surface of the view.
+    pub fn capture(&self, view: ViewId) -> Result<RgbaImage, ActorError> {
+        self.call(|reply| Command::Capture { view, reply })?
+    }
+}
+
+impl RendererHandle {
+    pub(crate) fn send(&self, command: Command) -> Result<(), ActorError> {
+        self.tx.send(command).map_err(|_| ActorError::Disconnected)
+    }
+
+    fn call<T: Send + 'static>(
+        &self,
+        command: impl FnOnce(Reply<T>) -> Command,
+    ) -> Result<T, ActorError> {
+        let rx = self.request(command)?;
+        rx.recv().map_err(|_| ActorError::Disconnected)
+    }
+
+    fn call_timeout<T: Send + 'static>(
+        &self,
+        command: impl FnOnce(Reply<T>) -> Command,
+        timeout: Duration,
+    ) -> Result<T, ActorError> {
+        let rx = self.request(command)?;
+        rx.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => ActorError::Timeout,
+            RecvTimeoutError::Disconnected => ActorError::Disconnected,
+        })
+    }
+
+    fn request<T: Send + 'static>(
+        &self,
+        command: impl FnOnce(Reply<T>) -> Command,
+    ) -> Result<Receiver<T>, ActorError> {
+        let (tx, rx) = mpsc::channel();
+        // a late reply after a timeout is dropped
+        self.send(command(Box::new(move |value| {
+            let _ = tx.send(value);
+        })))?;
+        Ok(rx)
+    }
+}
+
+enum LoadEvent {
+    Begin,
+    Done(Result<(), ActorError>),
+}
+
+/// Main frame loads of a view, matches load events with the command that started the load.
+#[derive(Default)]
+struct Loads {
+    /// Main frame loads begun so far, the generation of the current load.
+    begun: u64,
+    /// Reply of the awaited load and the generation it will have once begun.
+    pending: Option<(u64, Reply<Result<(), ActorError>>)>,
+}
+
+impl Loads {
+    /// Start awaiting the next load, cancels the previous one.
+    fn start(&mut self, reply: Reply<Result<(), ActorError>>) {
+        if let Some((_, previous)) = self.pending.replace((self.begun + 1, reply)) {
+            previous(Err(ActorError::Cancelled));
+        }
+    }
+
+    fn handle(&mut self, event: LoadEvent) {
+        match event {
+            LoadEvent::Begin => self.begun += 1,
+            // events of loads begun before the awaited one are stale
+            LoadEvent::Done(result) => match self.pending.take() {
+                Some((generation, reply)) if self.begun >= generation => reply(result),
+                pending => self.pending = pending,
+            },
+        }
+    }
+}
+
+fn run(renderer: &Rc<Renderer>, rx: &Receiver<Command>, tick: Duration) {
+    let events: RefCell<Vec<(ULView, LoadEvent)>> = RefCell::new(Vec::new());
+    let on_begin = |caller, _, is_main_frame, _: &str| {
+        if is_main_frame {
+            events.borrow_mut().push((caller, LoadEvent::Begin));
+        }
+    };
+    let on_finish = |caller, _, is_main_frame, _: &str| {
+        if is_main_frame {
+            events.borrow_mut().push((caller, LoadEvent::Done(Ok(()))));
+        }
+    };
+    let on_fail = |caller, _, is_main_frame, url: &str, description: &str, _: &str, error_code| {
+        if is_main_frame {
+            let error = ActorError::LoadFailed {
+                url: url.to_owned(),
+                description: description.to_owned(),
+                error_code,
+            };
+            events
+                .borrow_mut()
+                .push((caller, LoadEvent::Done(Err(error))));
+        }
+    };
+
+    // declared after the callbacks, so views are dropped before them
+    let mut views: HashMap<ViewId, View> = HashMap::new();
+    let mut loads: HashMap<ViewId, Loads> = HashMap::new();
+    let mut next_id = 0;
+
+    loop {
+        let command = match rx.recv_timeout(tick) {
+            Ok(command) => Some(command),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        match command {
+            None => {}
+            Some(Command::Shutdown) => break,
+            Some(Command::CreateView {
+                width,
+                height,
+                config,
+                reply,
+            }) => {
+                let session = renderer.clone().default_session();
+                let mut view = match session.try_create_view(width, height, &config) {
+                    Ok(view) => view,
+                    Err(e) => {
+                        reply(Err(e.into()));
+                        continue;
+                    }
+                };
+                view.set_begin_loading_callback(&on_begin);
+                view.set_finish_loading_callback(&on_finish);
+                view.set_fail_loading_callback(&on_fail);
+
+                let id = ViewId(next_id);
+                next_id += 1;
+                views.insert(id, view);
+                loads.insert(id, Loads::default());
+                reply(Ok(id));
+            }
+            Some(Command::DestroyView { view }) => {
+                views.remove(&view);
+                if let Some((_, reply)) = loads.remove(&view).and_then(|l| l.pending) {
+                    reply(Err(ActorError::Cancelled));
+                }
+            }
+            Some(Command::LoadHtml { view, html, reply }) => match views.get_mut(&view) {
+                Some(v) => {
+                    loads.entry(view).or_default().start(reply);
+                    v.load_html(&html);
+                }
+                None => reply(Err(ActorError::UnknownView(view))),
+            },
+            Some(Command::LoadUrl { view, url, reply }) => match views.get_mut(&view) {
+                Some(v) => {
+                    loads.entry(view).or_default().start(reply);
+                    v.load_url(&url);
+                }
+                None => reply(Err(ActorError::UnknownView(view))),
+            },
+            Some(Command::Resize {
+                view,
+                width,
+                height,
+                reply,
+            }) => reply(match views.get_mut(&view) {
+                Some(v) => {
+                    v.resize(width, height);
+                    Ok(())
+                }
+                None => Err(ActorError::UnknownView(view)),
+            }),
+            Some(Command::Evaluate {
+                view,
+                script,
+                reply,
+            }) => reply(match views.get_mut(&view) {
+                Some(v) => Ok(v.evaluate_script(&script)),
+                None => Err(ActorError::UnknownView(view)),
+            }),
+            Some(Command::Capture { view, reply }) => reply(match views.get_mut(&view) {
+                Some(v) => {
+                    renderer.update();
+                    renderer.render_view(v);
+                    Ok(RgbaImage::from_surface(&mut v.surface()))
+                }
+                None => Err(ActorError::UnknownView(view)),
+            }),
+        }
+
+        renderer.update();
+
+        for (caller, event) in events.borrow_mut().drain(..) {
+            let id = views
+                .iter()
+                .find(|(_, v)| v.as_raw_ptr() == caller)
+                .map(|(id, _)| *id);
+            if let Some(loads) = id.and_then(|id| loads.get_mut(&id)) {
+                loads.handle(event);
+            }
+        }
+    }
+
+    for (_, reply) in loads.into_values().filter_map(|l| l.pending) {
+        reply(Err(ActorError::Disconnected));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_times_out_without_reply() {
+        let (tx, rx) = mpsc::channel();
+        let handle = RendererHandle { tx };
+
+        let result = handle.load_html_timeout(ViewId(0), "", Duration::from_millis(10));
+        assert_eq!(result, Err(ActorError::Timeout));
+        // the command was sent, its reply is just never awaited
+        assert!(matches!(rx.try_recv(), Ok(Command::LoadHtml { .. })));
+
+        drop(rx);
+        let result = handle.load_url_timeout(ViewId(0), "about:blank", Duration::from_secs(1));
+        assert_eq!(result, Err(ActorError::Disconnected));
+    }
+}
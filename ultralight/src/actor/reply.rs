@@ -0,0 +1,77 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+};
+
+use crate::actor::ActorError;
+
+struct Shared<T> {
+    value: Option<T>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+fn lock<T>(shared: &Mutex<Shared<T>>) -> MutexGuard<'_, Shared<T>> {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub(crate) fn channel<T>() -> (ReplySender<T>, ReplyFuture<T>) {
+    let shared = Arc::new(Mutex::new(Shared {
+        value: None,
+        closed: false,
+        waker: None,
+    }));
+
+    (ReplySender(shared.clone()), ReplyFuture(shared))
+}
+
+pub(crate) struct ReplySender<T>(Arc<Mutex<Shared<T>>>);
+
+impl<T> ReplySender<T> {
+    pub(crate) fn send(self, value: T) {
+        lock(&self.0).value = Some(value);
+        // waking happens in drop
+    }
+}
+
+impl<T> Drop for ReplySender<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut shared = lock(&self.0);
+            shared.closed = true;
+            shared.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Resolves once the renderer thread replied, or with [`ActorError::Disconnected`] if it exited
+/// first. Works with any executor.
+pub struct ReplyFuture<T>(Arc<Mutex<Shared<T>>>);
+
+impl<T> Future for ReplyFuture<T> {
+    type Output = Result<T, ActorError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = lock(&self.0);
+
+        if let Some(value) = shared.value.take() {
+            return Poll::Ready(Ok(value));
+        }
+        if shared.closed {
+            return Poll::Ready(Err(ActorError::Disconnected));
+        }
+
+        match &shared.waker {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            _ => shared.waker = Some(cx.waker().clone()),
+        }
+
+        Poll::Pending
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    sync::{Mutex, PoisonError},
+    thread::{self, ThreadId},
+};
+
+/// The thread that created the live [`Renderer`](crate::renderer::Renderer).
+static OWNER: Mutex<Option<ThreadId>> = Mutex::new(None);
+
+pub(crate) fn set_owner_thread() {
+    *OWNER.lock().unwrap_or_else(PoisonError::into_inner) = Some(thread::current().id());
+}
+
+pub(crate) fn clear_owner_thread() {
+    *OWNER.lock().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Ultralight is single threaded, everything but the platform handlers (logger, file system)
+/// must be called from the thread that created the renderer. Only checked in debug builds.
+///
+/// Check where callbacks are registered, not inside them: they run during
+/// [`Renderer::update`](crate::renderer::Renderer::update) on the owner thread anyway, and a
+/// panic must not unwind into Ultralight.
+#[inline]
+#[track_caller]
+pub(crate) fn assert_owner_thread() {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let owner = *OWNER.lock().unwrap_or_else(PoisonError::into_inner);
+    let current = thread::current();
+
+    if let Some(owner) = owner {
+        if owner != current.id() {
+            panic!(
+                "Ultralight called from thread {:?} ({}), but the Renderer was created on {owner:?}. \
+                 Renderer, Session, View and surfaces must stay on the thread that created the \
+                 Renderer, see `ultralight::actor` to use it from other threads.",
+                current.id(),
+                current.name().unwrap_or("unnamed"),
+            );
+        }
+    }
+}
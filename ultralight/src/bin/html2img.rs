@@ -0,0 +1,372 @@
+use std::{
+    cell::Cell,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use ultralight::{
+    config::{Config, ViewConfig},
+    image::RgbaImage,
+    logger::LogLevel,
+    platform::Platform,
+    renderer::Renderer,
+    view::View,
+};
+
+/// Render HTML to a PNG or JPEG image.
+#[derive(Parser)]
+#[command(name = "html2img", version)]
+struct Args {
+    /// HTML file under `--base-dir`, `-` to read HTML from stdin, or a URL (eg.
+    /// `file:///page.html`)
+    input: String,
+
+    /// Output image, the format is picked by extension (.png, .jpg, .jpeg)
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Viewport width in CSS pixels
+    #[arg(long, default_value_t = 1024)]
+    width: u32,
+
+    /// Viewport height in CSS pixels
+    #[arg(long, default_value_t = 768)]
+    height: u32,
+
+    /// Device scale, eg. 2.0 renders at 200%
+    #[arg(long, default_value_t = 1.0)]
+    scale: f64,
+
+    /// Grow the viewport to the full document height
+    #[arg(long)]
+    full_page: bool,
+
+    /// Don't paint a background, only meaningful for PNG output
+    #[arg(long)]
+    transparent: bool,
+
+    /// CSS file applied to every page
+    #[arg(long)]
+    user_stylesheet: Option<PathBuf>,
+
+    /// User-Agent sent with requests and exposed as `navigator.userAgent`
+    #[arg(long)]
+    user_agent: Option<String>,
+
+    /// JavaScript expression, capture once it evaluates to a truthy value
+    #[arg(long)]
+    wait_for: Option<String>,
+
+    /// Extra time to wait after the page is ready, in milliseconds
+    #[arg(long, default_value_t = 0)]
+    delay: u64,
+
+    /// Give up after this many milliseconds
+    #[arg(long, default_value_t = 30_000)]
+    timeout: u64,
+
+    /// Crop to the bounding box of the first element matching this CSS selector
+    #[arg(long)]
+    selector: Option<String>,
+
+    /// JPEG quality
+    #[arg(long, default_value_t = 90, value_parser = clap::value_parser!(u8).range(1..=100))]
+    quality: u8,
+
+    /// Root of the file system `file:///` URLs resolve against, must contain `resources/`
+    #[arg(long, default_value = ".")]
+    base_dir: PathBuf,
+}
+
+enum Input {
+    Html(String),
+    Url(String),
+}
+
+enum Format {
+    Png,
+    Jpeg,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("html2img: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let format = output_format(&args.output)?;
+    let input = read_input(&args.input, &args.base_dir)?;
+
+    let mut config = Config::default();
+    if let Some(path) = &args.user_stylesheet {
+        let css =
+            fs::read_to_string(path).map_err(|e| format!("can't read {}: {e}", path.display()))?;
+        config.set_user_stylesheet(&css);
+    }
+    let renderer = Platform::builder()
+        .logger(|level, msg| {
+            if let LogLevel::Error = level {
+                eprintln!("{msg}")
+            };
+        })
+        .default_file_system(&args.base_dir)
+        .config(config)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut view_config = ViewConfig::default();
+    view_config.set_is_transparent(args.transparent);
+    view_config.set_initial_device_scale(args.scale);
+    if let Some(user_agent) = &args.user_agent {
+        view_config.set_user_agent(user_agent);
+    }
+
+    let loaded: Cell<Option<Result<(), String>>> = Cell::new(None);
+    let on_finish = |_, _, is_main_frame, _: &str| {
+        if is_main_frame {
+            loaded.set(Some(Ok(())));
+        }
+    };
+    let on_fail = |_, _, is_main_frame, url: &str, description: &str, _: &str, error_code| {
+        if is_main_frame {
+            loaded.set(Some(Err(format!(
+                "failed to load {url}: {description} ({error_code})"
+            ))));
+        }
+    };
+
+    let session = renderer.clone().default_session();
+    let (width, height) = (
+        to_device(args.width, args.scale),
+        to_device(args.height, args.scale),
+    );
+    let mut view = session
+        .try_create_view(width, height, &view_config)
+        .map_err(|e| e.to_string())?;
+    view.set_finish_loading_callback(&on_finish);
+    view.set_fail_loading_callback(&on_fail);
+
+    match &input {
+        Input::Html(html) => view.load_html(html),
+        Input::Url(url) => view.load_url(url),
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(args.timeout);
+    pump(&renderer, deadline, || loaded.take())??;
+
+    if let Some(expr) = &args.wait_for {
+        let script = format!("!!({expr})");
+        pump(&renderer, deadline, || {
+            matches!(view.evaluate_script(&script).as_deref(), Ok("true")).then_some(())
+        })?;
+    }
+
+    if args.delay > 0 {
+        let until = Instant::now() + Duration::from_millis(args.delay);
+        pump(&renderer, deadline, || {
+            (Instant::now() >= until).then_some(())
+        })?;
+    }
+
+    if args.full_page {
+        let script = "Math.max(document.documentElement.scrollHeight, \
+                      document.body ? document.body.scrollHeight : 0)";
+        let page_height = evaluate_numbers(&mut view, script)?[0];
+        let page_height = (page_height * args.scale).ceil() as u32;
+        if page_height > height {
+            view.resize(width, page_height);
+        }
+    }
+
+    renderer.update();
+    renderer.render();
+
+    let mut image = RgbaImage::from_surface(&mut view.surface());
+
+    if let Some(selector) = &args.selector {
+        image = crop_to_selector(&mut view, &image, selector, args.scale)?;
+    }
+
+    match format {
+        Format::Png => {
+            if args.transparent {
+                image.unpremultiply_alpha();
+            }
+            image
+                .write_png(&args.output)
+                .map_err(|e| format!("can't write {}: {e}", args.output.display()))
+        }
+        Format::Jpeg => write_jpeg(&image, &args.output, args.quality),
+    }
+}
+
+fn output_format(path: &Path) -> Result<Format, String> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("png") => Ok(Format::Png),
+        Some("jpg" | "jpeg") => Ok(Format::Jpeg),
+        _ => Err(format!(
+            "unknown output format for {}, use .png, .jpg or .jpeg",
+            path.display()
+        )),
+    }
+}
+
+fn read_input(input: &str, base_dir: &Path) -> Result<Input, String> {
+    if input == "-" {
+        let mut html = String::new();
+        io::stdin()
+            .read_to_string(&mut html)
+            .map_err(|e| format!("can't read stdin: {e}"))?;
+        return Ok(Input::Html(html));
+    }
+
+    if input.contains("://") {
+        return Ok(Input::Url(input.to_owned()));
+    }
+
+    // loaded by URL, so relative links to images and stylesheets resolve next to the file
+    file_url(Path::new(input), base_dir).map(Input::Url)
+}
+
+/// `file:///` URL of `path`, relative to `base_dir` which the file system is rooted at.
+fn file_url(path: &Path, base_dir: &Path) -> Result<String, String> {
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("can't read {}: {e}", path.display()))?;
+    let base_dir = base_dir
+        .canonicalize()
+        .map_err(|e| format!("can't read {}: {e}", base_dir.display()))?;
+    let relative = path.strip_prefix(&base_dir).map_err(|_| {
+        format!(
+            "{} is outside of the base dir {}, see --base-dir",
+            path.display(),
+            base_dir.display()
+        )
+    })?;
+
+    let mut url = "file://".to_owned();
+    for component in relative.components() {
+        url.push('/');
+        for b in component.as_os_str().to_string_lossy().bytes() {
+            match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    url.push(b as char)
+                }
+                b => url.push_str(&format!("%{b:02X}")),
+            }
+        }
+    }
+    Ok(url)
+}
+
+fn to_device(css_pixels: u32, scale: f64) -> u32 {
+    (f64::from(css_pixels) * scale).round().max(1.0) as u32
+}
+
+/// Keep updating the renderer until `poll` yields a value.
+fn pump<T>(
+    renderer: &Renderer,
+    deadline: Instant,
+    mut poll: impl FnMut() -> Option<T>,
+) -> Result<T, String> {
+    loop {
+        renderer.update();
+
+        if let Some(value) = poll() {
+            return Ok(value);
+        }
+        if Instant::now() > deadline {
+            return Err("timed out".to_owned());
+        }
+
+        sleep(Duration::from_millis(1));
+    }
+}
+
+/// Evaluate a script returning a number or an array of numbers.
+fn evaluate_numbers(view: &mut View, script: &str) -> Result<Vec<f64>, String> {
+    let result = view
+        .evaluate_script(&format!("String({script})"))
+        .map_err(|e| format!("script failed: {e}"))?;
+
+    result
+        .split(',')
+        .map(|n| n.trim().parse::<f64>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("unexpected script result `{result}`"))
+}
+
+fn crop_to_selector(
+    view: &mut View,
+    image: &RgbaImage,
+    selector: &str,
+    scale: f64,
+) -> Result<RgbaImage, String> {
+    let script = format!(
+        "(() => {{ \
+            const e = document.querySelector({}); \
+            if (!e) return 'none'; \
+            const r = e.getBoundingClientRect(); \
+            return [r.left, r.top, r.width, r.height]; \
+        }})()",
+        js_string(selector)
+    );
+    let rect = evaluate_numbers(view, &script)
+        .map_err(|_| format!("selector `{selector}` matched no element"))?;
+
+    let [left, top, width, height] = [rect[0], rect[1], rect[2], rect[3]].map(|v| v * scale);
+    let (x, y) = (left.max(0.0).floor(), top.max(0.0).floor());
+    let (right, bottom) = ((left + width).ceil(), (top + height).ceil());
+    if right <= x || bottom <= y {
+        return Err(format!("selector `{selector}` matched an empty element"));
+    }
+
+    Ok(image.crop(x as u32, y as u32, (right - x) as u32, (bottom - y) as u32))
+}
+
+fn write_jpeg(image: &RgbaImage, path: &Path, quality: u8) -> Result<(), String> {
+    let (width, height) = match (u16::try_from(image.width()), u16::try_from(image.height())) {
+        (Ok(width), Ok(height)) => (width, height),
+        _ => return Err("image is too large for JPEG".to_owned()),
+    };
+
+    let encoder = jpeg_encoder::Encoder::new_file(path, quality)
+        .map_err(|e| format!("can't write {}: {e}", path.display()))?;
+    encoder
+        .encode(image.pixels(), width, height, jpeg_encoder::ColorType::Rgba)
+        .map_err(|e| format!("can't write {}: {e}", path.display()))
+}
+
+/// Quote `s` as a JavaScript string literal.
+fn js_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() || c == '\u{2028}' || c == '\u{2029}' => {
+                quoted.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
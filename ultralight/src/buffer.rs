@@ -1,5 +1,6 @@
-use std::{ffi::c_void, mem, ops::Deref, ptr, rc::Rc, slice};
+use std::{ffi::c_void, mem, ops::Deref, ptr, rc::Rc, slice, sync::Arc};
 
+use memmap2::Mmap;
 use ultralight_sys::*;
 
 // we store the data length as a userdata address
@@ -8,6 +9,17 @@ unsafe extern "C" fn destroy_borrowed_buffer(user_data: *mut c_void, data: *mut
     drop(Rc::from_raw(data_ptr))
 }
 
+// same as `destroy_borrowed_buffer`, for `Arc`
+unsafe extern "C" fn destroy_shared_buffer(user_data: *mut c_void, data: *mut c_void) {
+    let data_ptr = ptr::slice_from_raw_parts(data as *const u8, user_data as usize);
+    drop(Arc::from_raw(data_ptr))
+}
+
+// user data is the boxed storage
+unsafe extern "C" fn destroy_boxed_buffer<T>(user_data: *mut c_void, _data: *mut c_void) {
+    drop(Box::from_raw(user_data as *mut T))
+}
+
 #[derive(Debug)]
 pub struct Buffer(ULBuffer);
 
@@ -18,6 +30,8 @@ impl Buffer {
         Self(buffer)
     }
 
+    /// NOTICE: [`Rc`] is not thread safe, use [`Buffer::new_shared`] if the buffer can be
+    /// dropped by Ultralight on another thread, eg. from a [`FileSystem`](crate::filesystem::FileSystem).
     pub fn new_borrowed(data: Rc<[u8]>) -> Self {
         let data_len = data.len(); // fat ptr len is unstable, so get it from slice
         let data = Rc::into_raw(data);
@@ -33,6 +47,66 @@ impl Buffer {
         Self(buffer)
     }
 
+    /// Like [`Buffer::new_borrowed`], for data shared across threads, eg. by a
+    /// [`FileSystem`](crate::filesystem::FileSystem).
+    pub fn new_shared(data: Arc<[u8]>) -> Self {
+        let data_len = data.len();
+        let data = Arc::into_raw(data);
+        let buffer = unsafe {
+            ulCreateBuffer(
+                data as *mut _,
+                data_len,
+                // we store the data length as a userdata address
+                data_len as _,
+                Some(destroy_shared_buffer),
+            )
+        };
+        Self(buffer)
+    }
+
+    /// Wrap data that lives for the whole program, eg. from `include_bytes!`, without copying.
+    pub fn new_static(data: &'static [u8]) -> Self {
+        let buffer =
+            unsafe { ulCreateBuffer(data.as_ptr() as *mut _, data.len(), ptr::null_mut(), None) };
+        Self(buffer)
+    }
+
+    /// Take ownership of `storage` without copying, it is dropped when Ultralight destroys the
+    /// buffer.
+    pub fn new_boxed<T: AsRef<[u8]> + Send + 'static>(storage: T) -> Self {
+        // box first, the data of eg. an array moves with it
+        let storage = Box::new(storage);
+        let (data, len) = {
+            let data = (*storage).as_ref();
+            (data.as_ptr(), data.len())
+        };
+        let storage = Box::into_raw(storage);
+        let buffer = unsafe {
+            ulCreateBuffer(
+                data as *mut _,
+                len,
+                storage.cast(),
+                Some(destroy_boxed_buffer::<T>),
+            )
+        };
+        Self(buffer)
+    }
+
+    pub fn new_vec(data: Vec<u8>) -> Self {
+        Self::new_boxed(data)
+    }
+
+    #[cfg(feature = "bytes")]
+    pub fn new_bytes(data: bytes::Bytes) -> Self {
+        Self::new_boxed(data)
+    }
+
+    /// Wrap a memory mapped file without copying, it is unmapped when Ultralight destroys the
+    /// buffer.
+    pub fn new_mmap(mmap: Mmap) -> Self {
+        Self::new_boxed(mmap)
+    }
+
     pub fn owns_data(&self) -> bool {
         let Self(buffer) = self;
         unsafe { ulBufferOwnsData(*buffer) }
@@ -69,3 +143,34 @@ impl Deref for Buffer {
         unsafe { slice::from_raw_parts(data.cast(), len) }
     }
 }
+
+impl From<Vec<u8>> for Buffer {
+    fn from(data: Vec<u8>) -> Self {
+        Self::new_vec(data)
+    }
+}
+
+impl From<Arc<[u8]>> for Buffer {
+    fn from(data: Arc<[u8]>) -> Self {
+        Self::new_shared(data)
+    }
+}
+
+impl From<&'static [u8]> for Buffer {
+    fn from(data: &'static [u8]) -> Self {
+        Self::new_static(data)
+    }
+}
+
+impl From<Mmap> for Buffer {
+    fn from(mmap: Mmap) -> Self {
+        Self::new_mmap(mmap)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<bytes::Bytes> for Buffer {
+    fn from(data: bytes::Bytes) -> Self {
+        Self::new_bytes(data)
+    }
+}
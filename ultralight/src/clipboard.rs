@@ -0,0 +1,67 @@
+use std::{
+    mem::ManuallyDrop,
+    ops::Deref,
+    panic::{self, AssertUnwindSafe},
+    sync::{PoisonError, RwLock},
+};
+
+use ultralight_sys::*;
+
+use crate::{string::UString, AsULRawPtr};
+
+static USER_CLIPBOARD: RwLock<Option<Box<dyn Clipboard>>> = RwLock::new(None);
+static CLIPBOARD: ULClipboard = ULClipboard {
+    clear: Some(clear_callback),
+    read_plain_text: Some(read_plain_text_callback),
+    write_plain_text: Some(write_plain_text_callback),
+};
+
+/// Run `f` with the user clipboard, if any. A panic must not unwind into Ultralight, it is
+/// caught and the call does nothing.
+fn with_clipboard(f: impl FnOnce(&dyn Clipboard)) {
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        let guard = USER_CLIPBOARD
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(clipboard) = guard.as_ref() {
+            f(&**clipboard);
+        }
+    }));
+}
+
+unsafe extern "C" fn clear_callback() {
+    with_clipboard(|clipboard| clipboard.clear());
+}
+
+unsafe extern "C" fn read_plain_text_callback(result: ULString) {
+    with_clipboard(|clipboard| {
+        let text = UString::from(clipboard.read_plain_text());
+        unsafe { ulStringAssignString(result, text.as_raw_ptr()) };
+    });
+}
+
+unsafe extern "C" fn write_plain_text_callback(text: ULString) {
+    let text = ManuallyDrop::new(UString::from_raw(text));
+    with_clipboard(|clipboard| clipboard.write_plain_text(text.deref()));
+}
+
+pub trait Clipboard: Send + Sync {
+    /// Clear the clipboard.
+    fn clear(&self);
+
+    /// Read plain text from the clipboard.
+    fn read_plain_text(&self) -> String;
+
+    /// Write plain text to the clipboard.
+    fn write_plain_text(&self, text: &str);
+}
+
+pub(crate) fn set_platform_clipboard(clipboard: Box<dyn Clipboard>) {
+    *USER_CLIPBOARD
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(clipboard);
+
+    unsafe {
+        ulPlatformSetClipboard(CLIPBOARD);
+    }
+}
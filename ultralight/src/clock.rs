@@ -0,0 +1,185 @@
+use std::{
+    cell::{Cell, RefCell},
+    ffi,
+    ptr::null_mut,
+    time::Duration,
+};
+
+use ultralight_sys::*;
+
+use crate::{string::UString, AsULRawPtr};
+
+/// Replaces `Date`, `performance.now`, timers and `requestAnimationFrame` of the page with
+/// versions driven by `__ultralightClock.advance(ms)`. CSS/Web Animations are paused and seeked
+/// to the virtual time on every advance.
+const CLOCK_SCRIPT: &str = r#"(function (now, epoch) {
+  if (globalThis.__ultralightClock) return;
+
+  const NativeDate = Date;
+  const timers = new Map();
+  const born = new WeakMap();
+  let frames = new Map();
+  let nextId = 1, seq = 0, depth = 0;
+
+  function run(fn, args) {
+    depth++;
+    try {
+      typeof fn === "function" ? fn(...args) : (0, eval)(String(fn));
+    } catch (e) {
+      console.error(e);
+    } finally {
+      depth--;
+    }
+  }
+
+  function schedule(fn, delay, args, repeat) {
+    delay = Math.max(depth > 0 || repeat ? 1 : 0, Number(delay) || 0);
+    const id = nextId++;
+    timers.set(id, { due: now + delay, seq: seq++, fn, args, interval: repeat ? delay : null });
+    return id;
+  }
+
+  function VirtualDate(...args) {
+    if (!new.target) return new NativeDate(epoch + now).toString();
+    return args.length ? new NativeDate(...args) : new NativeDate(epoch + now);
+  }
+  VirtualDate.prototype = NativeDate.prototype;
+  VirtualDate.now = () => epoch + now;
+  VirtualDate.parse = NativeDate.parse;
+  VirtualDate.UTC = NativeDate.UTC;
+
+  globalThis.Date = VirtualDate;
+  performance.now = () => now;
+  globalThis.setTimeout = (fn, delay, ...args) => schedule(fn, delay, args, false);
+  globalThis.setInterval = (fn, delay, ...args) => schedule(fn, delay, args, true);
+  globalThis.clearTimeout = globalThis.clearInterval = (id) => timers.delete(id);
+  globalThis.requestAnimationFrame = (fn) => (frames.set(nextId, fn), nextId++);
+  globalThis.cancelAnimationFrame = (id) => frames.delete(id);
+
+  globalThis.__ultralightClock = {
+    advance(ms) {
+      const from = now, target = now + ms;
+      for (;;) {
+        let next = null;
+        for (const entry of timers) {
+          const t = entry[1];
+          if (t.due > target) continue;
+          if (!next || t.due < next[1].due || (t.due === next[1].due && t.seq < next[1].seq)) next = entry;
+        }
+        if (!next) break;
+
+        const [id, t] = next;
+        now = Math.max(now, t.due);
+        if (t.interval === null) {
+          timers.delete(id);
+        } else {
+          t.due += t.interval;
+          t.seq = seq++;
+        }
+        run(t.fn, t.args);
+      }
+      now = target;
+
+      const callbacks = frames;
+      frames = new Map();
+      for (const fn of callbacks.values()) run(fn, [now]);
+
+      if (document.getAnimations) {
+        for (const animation of document.getAnimations()) {
+          if (!born.has(animation)) born.set(animation, from);
+          animation.pause();
+          animation.currentTime = now - born.get(animation);
+        }
+      }
+    },
+  };
+})"#;
+
+pub(crate) struct VirtualClock {
+    enabled: Cell<bool>,
+    epoch: Cell<Duration>,
+    elapsed: Cell<Duration>,
+    views: RefCell<Vec<ULView>>,
+}
+
+impl VirtualClock {
+    pub(crate) fn new() -> Self {
+        Self {
+            enabled: Cell::new(false),
+            epoch: Cell::new(Duration::ZERO),
+            elapsed: Cell::new(Duration::ZERO),
+            views: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn enable(&self, epoch: Duration) {
+        self.enabled.set(true);
+        self.epoch.set(epoch);
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.elapsed.get()
+    }
+
+    pub(crate) fn register(&self, view: ULView) {
+        self.views.borrow_mut().push(view);
+
+        unsafe {
+            ulViewSetWindowObjectReadyCallback(
+                view,
+                Some(window_object_ready_callback),
+                self as *const _ as *mut _,
+            );
+        }
+    }
+
+    pub(crate) fn unregister(&self, view: ULView) {
+        self.views.borrow_mut().retain(|v| *v != view);
+    }
+
+    pub(crate) fn advance(&self, duration: Duration) {
+        self.elapsed.set(self.elapsed.get() + duration);
+
+        let script = UString::from(format!(
+            "globalThis.__ultralightClock && __ultralightClock.advance({})",
+            duration.as_secs_f64() * 1000.0
+        ));
+        for view in self.views.borrow().iter() {
+            evaluate(*view, &script);
+        }
+    }
+
+    fn inject(&self, view: ULView) {
+        let script = UString::from(format!(
+            "{CLOCK_SCRIPT}({}, {})",
+            self.elapsed().as_secs_f64() * 1000.0,
+            self.epoch.get().as_millis()
+        ));
+        evaluate(view, &script);
+    }
+}
+
+unsafe extern "C" fn window_object_ready_callback(
+    user_data: *mut ffi::c_void,
+    caller: ULView,
+    _frame_id: ffi::c_ulonglong,
+    is_main_frame: bool,
+    _url: ULString,
+) {
+    if !is_main_frame {
+        return;
+    }
+
+    let clock = unsafe { &*(user_data as *const VirtualClock) };
+    clock.inject(caller);
+}
+
+fn evaluate(view: ULView, script: &UString) {
+    let mut exception: ULString = null_mut();
+    // both strings are owned by the view, nothing to release
+    unsafe { ulViewEvaluateScript(view, script.as_raw_ptr(), &mut exception) };
+}
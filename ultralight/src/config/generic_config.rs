@@ -14,7 +14,8 @@ macro_rules! lock_in_scope {
     };
 }
 
-pub struct Config(ULConfig, Mutex<()>);
+// the resource path prefix is kept, Ultralight has no getter for it
+pub struct Config(ULConfig, Mutex<()>, String);
 
 // SAFETY: ultralight can only run on systems, that c_int = i32.
 #[allow(clippy::unnecessary_cast)]
@@ -69,6 +70,7 @@ impl Config {
         lock_in_scope!(self.1);
         let s = UString::from(resource_path_prefix);
         unsafe { ulConfigSetResourcePathPrefix(self.0, s.as_raw_ptr()) }
+        self.2 = resource_path_prefix.to_owned();
     }
 
     /// The winding order for front-facing triangles.
@@ -231,6 +233,12 @@ impl Config {
     }
 }
 
+impl Config {
+    pub(crate) fn resource_path_prefix(&self) -> &str {
+        &self.2
+    }
+}
+
 impl AsULRawPtr<ULConfig> for Config {
     fn as_raw_ptr(&self) -> ULConfig {
         self.0
@@ -251,9 +259,16 @@ impl Debug for Config {
 
 impl Default for Config {
     fn default() -> Self {
-        Self(unsafe { ulCreateConfig() }, Mutex::new(()))
+        Self(
+            unsafe { ulCreateConfig() },
+            Mutex::new(()),
+            "resources/".to_owned(),
+        )
     }
 }
 
+// SAFETY: a ULConfig is a heap struct of settings, created before any renderer and not bound to
+// a thread, so it can be moved to the thread creating the renderer. It is only written through
+// `&mut self` setters, and only read through `&self` by `ulCreateRenderer`, which copies it.
 unsafe impl Send for Config {}
 unsafe impl Sync for Config {}
@@ -1,6 +1,9 @@
 use std::{
     fmt::{self, Debug, Formatter},
-    sync::Mutex,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
 };
 
 use ultralight_sys::*;
@@ -13,7 +16,9 @@ macro_rules! lock_in_scope {
     };
 }
 
-pub struct ViewConfig(ULViewConfig, Mutex<()>);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+pub struct ViewConfig(ULViewConfig, Mutex<()>, u64);
 
 impl ViewConfig {
     /// Whether to render using the GPU renderer (accelerated) or the CPU renderer (un-accelerated).
@@ -122,6 +127,14 @@ impl ViewConfig {
     }
 }
 
+impl ViewConfig {
+    /// Unique for the life of the process, identifies the config in a
+    /// [`ViewPool`](crate::pool::ViewPool).
+    pub(crate) fn id(&self) -> u64 {
+        self.2
+    }
+}
+
 impl AsULRawPtr<ULViewConfig> for ViewConfig {
     fn as_raw_ptr(&self) -> ULViewConfig {
         self.0
@@ -142,9 +155,16 @@ impl Debug for ViewConfig {
 
 impl Default for ViewConfig {
     fn default() -> Self {
-        Self(unsafe { ulCreateViewConfig() }, Mutex::new(()))
+        Self(
+            unsafe { ulCreateViewConfig() },
+            Mutex::new(()),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        )
     }
 }
 
+// SAFETY: a ULViewConfig only holds settings and no reference to a renderer or view, it can be
+// created on any thread and dropped on another. It is only written through `&mut self` setters,
+// `ulCreateView` reads it through `&self` and copies it into the view, and the id never changes.
 unsafe impl Send for ViewConfig {}
 unsafe impl Sync for ViewConfig {}
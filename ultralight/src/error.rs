@@ -0,0 +1,53 @@
+use std::{
+    error,
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
+
+/// Errors of the fallible (`try_*`) APIs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A [`Renderer`](crate::renderer::Renderer) is already alive, only one can exist at a time.
+    AlreadyLoaded,
+    /// Platform handlers can't change while a [`Renderer`](crate::renderer::Renderer) is alive.
+    RendererAlive,
+    /// Ultralight returned a null handle when creating the named object.
+    CreateFailed(&'static str),
+    /// [`PlatformBuilder`](crate::platform::PlatformBuilder) needs a file system to load the
+    /// bundled resources from.
+    MissingFileSystem,
+    /// A bundled resource is missing from the file system.
+    MissingResource(PathBuf),
+    /// The path can't be passed to Ultralight, eg. it contains a NUL byte.
+    InvalidPath(PathBuf),
+    WritePng(PathBuf),
+    /// All the views a [`ViewPool`](crate::pool::ViewPool) may create are checked out.
+    PoolExhausted,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyLoaded => write!(
+                f,
+                "a Renderer is already alive, only one can exist at a time"
+            ),
+            Self::RendererAlive => write!(
+                f,
+                "platform handlers can't be changed while a Renderer is alive"
+            ),
+            Self::CreateFailed(what) => write!(f, "Ultralight failed to create the {what}"),
+            Self::MissingFileSystem => write!(f, "no file system was provided"),
+            Self::MissingResource(path) => write!(
+                f,
+                "the file system doesn't provide {}, Ultralight can't start without it",
+                path.display()
+            ),
+            Self::InvalidPath(path) => write!(f, "invalid path {}", path.display()),
+            Self::WritePng(path) => write!(f, "failed to write PNG to {}", path.display()),
+            Self::PoolExhausted => write!(f, "all the views of the pool are checked out"),
+        }
+    }
+}
+
+impl error::Error for Error {}
@@ -0,0 +1,206 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError, RwLock},
+};
+
+use zip::ZipArchive;
+
+use crate::{
+    buffer::Buffer,
+    filesystem::{mime, relative_path, FileSystem},
+};
+
+enum Location {
+    Zip(usize),
+    Tar { offset: u64, size: u64 },
+}
+
+enum Source {
+    Zip(ZipArchive<File>),
+    Tar(File),
+}
+
+struct Archive {
+    source: Mutex<Source>,
+    index: HashMap<PathBuf, Location>,
+    cache: Mutex<HashMap<PathBuf, Arc<[u8]>>>,
+}
+
+/// [`FileSystem`] serving the files of a zip (stored or deflated) or tar archive.
+///
+/// The index is built when the archive is opened, entries are read (and decompressed) on first
+/// use and cached. Clones share the archive, [`ArchiveFileSystem::swap`] switches all of them to
+/// another archive, eg. to change the theme while the renderer is running.
+#[derive(Clone)]
+pub struct ArchiveFileSystem {
+    archive: Arc<RwLock<Arc<Archive>>>,
+}
+
+impl ArchiveFileSystem {
+    /// Open a `.zip` or `.tar` archive, by extension.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            archive: Arc::new(RwLock::new(Arc::new(Archive::open(path.as_ref())?))),
+        })
+    }
+
+    /// Replace the archive, files already handed to Ultralight stay valid.
+    pub fn swap(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let archive = Arc::new(Archive::open(path.as_ref())?);
+        *self.archive.write().unwrap_or_else(PoisonError::into_inner) = archive;
+        Ok(())
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.archive().index.keys().cloned().collect()
+    }
+
+    /// Content of a file, decompressed.
+    pub fn read(&self, path: &Path) -> io::Result<Arc<[u8]>> {
+        let path = relative_path(path).ok_or(io::ErrorKind::NotFound)?;
+        self.archive().read(&path)
+    }
+
+    /// Drop the decompressed entries, they are read again on next use.
+    pub fn clear_cache(&self) {
+        self.archive()
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+impl ArchiveFileSystem {
+    // the archive is cloned out so that reads don't block `swap`
+    fn archive(&self) -> Arc<Archive> {
+        self.archive
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl Archive {
+    fn open(path: &Path) -> io::Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+
+        let file = File::open(path)?;
+        let (source, index) = match extension.as_deref() {
+            Some("zip") => Self::index_zip(file)?,
+            Some("tar") => Self::index_tar(file)?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not a .zip or .tar archive", path.display()),
+                ))
+            }
+        };
+
+        Ok(Self {
+            source: Mutex::new(source),
+            index,
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn index_zip(file: File) -> io::Result<(Source, HashMap<PathBuf, Location>)> {
+        let mut zip = ZipArchive::new(file)?;
+        let mut index = HashMap::new();
+
+        for i in 0..zip.len() {
+            let entry = zip.by_index_raw(i)?;
+            // `enclosed_name` rejects `..` and absolute paths
+            if let Some(name) = entry.enclosed_name().filter(|_| entry.is_file()) {
+                index.insert(name, Location::Zip(i));
+            }
+        }
+
+        Ok((Source::Zip(zip), index))
+    }
+
+    fn index_tar(file: File) -> io::Result<(Source, HashMap<PathBuf, Location>)> {
+        let mut tar = tar::Archive::new(file);
+        let mut index = HashMap::new();
+
+        for entry in tar.entries()? {
+            let entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            if let Some(path) = relative_path(&entry.path()?) {
+                let location = Location::Tar {
+                    offset: entry.raw_file_position(),
+                    size: entry.size(),
+                };
+                index.insert(path, location);
+            }
+        }
+
+        Ok((Source::Tar(tar.into_inner()), index))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Arc<[u8]>> {
+        if let Some(data) = self.cached(path) {
+            return Ok(data);
+        }
+
+        // the cache isn't locked while decompressing, reads of cached files don't wait for it
+        let location = self.index.get(path).ok_or(io::ErrorKind::NotFound)?;
+        let mut source = self.source.lock().unwrap_or_else(PoisonError::into_inner);
+        let data: Arc<[u8]> = match (&mut *source, location) {
+            (Source::Zip(zip), &Location::Zip(i)) => {
+                let mut entry = zip.by_index(i)?;
+                let mut data = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut data)?;
+                data.into()
+            }
+            (Source::Tar(file), &Location::Tar { offset, size }) => {
+                let mut data = vec![0; size as usize];
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut data)?;
+                data.into()
+            }
+            _ => unreachable!("index and source are built together"),
+        };
+        drop(source);
+
+        // keep the entry of a concurrent read of the same file, so all callers share it
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(cache.entry(path.to_owned()).or_insert(data).clone())
+    }
+
+    fn cached(&self, path: &Path) -> Option<Arc<[u8]>> {
+        let cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.get(path).cloned()
+    }
+}
+
+impl FileSystem for ArchiveFileSystem {
+    fn file_exists(&self, path: &Path) -> bool {
+        relative_path(path).is_some_and(|path| self.archive().index.contains_key(&path))
+    }
+
+    fn get_file_mime_type(&self, path: &Path) -> String {
+        match mime::from_extension(path) {
+            Some(mime_type) => mime_type.to_owned(),
+            None => mime::mime_type(path, self.read(path).ok().as_deref()).to_owned(),
+        }
+    }
+
+    fn get_file_charset(&self, path: &Path) -> String {
+        let data = self.read(path).ok();
+        mime::charset(mime::mime_type(path, data.as_deref()), data.as_deref())
+    }
+
+    fn open_file(&self, path: &Path) -> io::Result<Buffer> {
+        self.read(path).map(Buffer::new_shared)
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::{buffer::Buffer, filesystem::FileSystem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    FileExists,
+    OpenFile,
+}
+
+/// A request made by Ultralight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessRecord {
+    pub kind: AccessKind,
+    pub path: PathBuf,
+    /// The file exists, or was opened.
+    pub success: bool,
+    /// Size of the opened file.
+    pub size: Option<usize>,
+    /// Why the request failed, [`io::ErrorKind::NotFound`] when the file doesn't exist.
+    pub error: Option<io::ErrorKind>,
+    pub latency: Duration,
+}
+
+/// Records of an [`AuditFileSystem`], shared with it.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    records: Arc<Mutex<Vec<AccessRecord>>>,
+}
+
+impl AuditLog {
+    pub fn records(&self) -> Vec<AccessRecord> {
+        self.lock().clone()
+    }
+
+    /// Return the records and clear the log.
+    pub fn take(&self) -> Vec<AccessRecord> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Paths that were requested but not found or failed to open, sorted and deduplicated.
+    pub fn missing(&self) -> Vec<PathBuf> {
+        let mut missing: Vec<PathBuf> = self
+            .lock()
+            .iter()
+            .filter(|r| !r.success)
+            .map(|r| r.path.clone())
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        missing
+    }
+
+    /// Whether `path` was opened successfully.
+    pub fn was_opened(&self, path: impl AsRef<Path>) -> bool {
+        self.lock()
+            .iter()
+            .any(|r| r.kind == AccessKind::OpenFile && r.success && r.path == path.as_ref())
+    }
+}
+
+impl AuditLog {
+    fn lock(&self) -> MutexGuard<'_, Vec<AccessRecord>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, record: AccessRecord) {
+        #[cfg(feature = "tracing")]
+        match record.success {
+            true => tracing::debug!(
+                target: "ultralight::filesystem",
+                kind = ?record.kind,
+                path = %record.path.display(),
+                size = record.size,
+                latency = ?record.latency,
+            ),
+            false => tracing::warn!(
+                target: "ultralight::filesystem",
+                kind = ?record.kind,
+                path = %record.path.display(),
+                error = ?record.error,
+                latency = ?record.latency,
+                "request failed",
+            ),
+        }
+
+        self.lock().push(record);
+    }
+}
+
+/// Wraps a [`FileSystem`] and records every `file_exists` and `open_file` request in an
+/// [`AuditLog`], and as `tracing` events with the `tracing` feature.
+pub struct AuditFileSystem<F> {
+    inner: F,
+    log: AuditLog,
+}
+
+impl<F: FileSystem> AuditFileSystem<F> {
+    pub fn new(inner: F) -> Self {
+        Self {
+            inner,
+            log: AuditLog::default(),
+        }
+    }
+
+    /// Handle to the records, keep it before passing the file system to Ultralight.
+    pub fn log(&self) -> AuditLog {
+        self.log.clone()
+    }
+
+    pub fn inner(&self) -> &F {
+        &self.inner
+    }
+}
+
+impl<F: FileSystem> FileSystem for AuditFileSystem<F> {
+    fn file_exists(&self, path: &Path) -> bool {
+        let start = Instant::now();
+        let exists = self.inner.file_exists(path);
+
+        self.log.push(AccessRecord {
+            kind: AccessKind::FileExists,
+            path: path.to_owned(),
+            success: exists,
+            size: None,
+            error: (!exists).then_some(io::ErrorKind::NotFound),
+            latency: start.elapsed(),
+        });
+        exists
+    }
+
+    fn get_file_mime_type(&self, path: &Path) -> String {
+        self.inner.get_file_mime_type(path)
+    }
+
+    fn get_file_charset(&self, path: &Path) -> String {
+        self.inner.get_file_charset(path)
+    }
+
+    fn open_file(&self, path: &Path) -> io::Result<Buffer> {
+        let start = Instant::now();
+        let buffer = self.inner.open_file(path);
+
+        self.log.push(AccessRecord {
+            kind: AccessKind::OpenFile,
+            path: path.to_owned(),
+            success: buffer.is_ok(),
+            size: buffer.as_ref().ok().map(|b| b.len()),
+            error: buffer.as_ref().err().map(io::Error::kind),
+            latency: start.elapsed(),
+        });
+        buffer
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use memmap2::Mmap;
+
+use crate::{
+    buffer::Buffer,
+    filesystem::{mime, relative_path, FileSystem},
+};
+
+/// [`FileSystem`] serving the files under a root directory.
+///
+/// Paths are resolved relative to the root, paths with `..` are rejected and so are symlinks
+/// pointing outside the root. Files are memory mapped, not copied.
+#[derive(Debug, Clone)]
+pub struct DirFileSystem {
+    root: PathBuf,
+}
+
+impl DirFileSystem {
+    pub fn new(root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref().canonicalize()?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolve `path` to a file inside the root, [`None`] if it doesn't exist or escapes it.
+    pub fn resolve(&self, path: &Path) -> Option<PathBuf> {
+        let relative = relative_path(path)?;
+
+        // canonicalize resolves symlinks, check the real location
+        let resolved = self.root.join(relative).canonicalize().ok()?;
+        match resolved.starts_with(&self.root) && resolved.is_file() {
+            true => Some(resolved),
+            false => None,
+        }
+    }
+}
+
+impl DirFileSystem {
+    /// First bytes of the file for sniffing, [`None`] if it can't be read.
+    fn head(&self, path: &Path) -> Option<Vec<u8>> {
+        let file = File::open(self.resolve(path)?).ok()?;
+        let mut head = Vec::with_capacity(mime::SNIFF_LEN);
+        file.take(mime::SNIFF_LEN as u64)
+            .read_to_end(&mut head)
+            .ok()?;
+        Some(head)
+    }
+
+    fn map(path: &Path) -> io::Result<Buffer> {
+        let file = File::open(path)?;
+        // mapping an empty file fails on some platforms
+        if file.metadata()?.len() == 0 {
+            return Ok(Buffer::new_owned([]));
+        }
+
+        // SAFETY: the file may be modified while mapped, like with any mmap, the content is
+        // only read by Ultralight.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(Buffer::new_mmap(mmap))
+    }
+}
+
+impl FileSystem for DirFileSystem {
+    fn file_exists(&self, path: &Path) -> bool {
+        self.resolve(path).is_some()
+    }
+
+    fn get_file_mime_type(&self, path: &Path) -> String {
+        match mime::from_extension(path) {
+            Some(mime_type) => mime_type.to_owned(),
+            None => mime::mime_type(path, self.head(path).as_deref()).to_owned(),
+        }
+    }
+
+    fn get_file_charset(&self, path: &Path) -> String {
+        let head = self.head(path);
+        let mime_type = mime::mime_type(path, head.as_deref());
+        mime::charset(mime_type, head.as_deref())
+    }
+
+    fn open_file(&self, path: &Path) -> io::Result<Buffer> {
+        let path = self.resolve(path).ok_or(io::ErrorKind::NotFound)?;
+        Self::map(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// `<tmp>/root/index.html` and `<tmp>/secret.txt` outside the root.
+    fn fixture() -> (tempfile::TempDir, DirFileSystem) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("root")).unwrap();
+        fs::write(dir.path().join("root/index.html"), "<p>hi</p>").unwrap();
+        fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+
+        let fs = DirFileSystem::new(dir.path().join("root")).unwrap();
+        (dir, fs)
+    }
+
+    #[test]
+    fn resolves_files_inside_root() {
+        let (_dir, fs) = fixture();
+
+        assert!(fs.file_exists(Path::new("index.html")));
+        assert!(fs.file_exists(Path::new("./index.html")));
+        assert_eq!(
+            fs.resolve(Path::new("index.html")),
+            Some(fs.root().join("index.html"))
+        );
+    }
+
+    #[test]
+    fn rejects_parent_dir() {
+        let (_dir, fs) = fixture();
+
+        assert_eq!(fs.resolve(Path::new("../secret.txt")), None);
+        assert_eq!(fs.resolve(Path::new("sub/../../secret.txt")), None);
+        assert!(!fs.file_exists(Path::new("../secret.txt")));
+    }
+
+    #[test]
+    fn absolute_paths_stay_inside_root() {
+        let (dir, fs) = fixture();
+
+        assert!(fs.file_exists(Path::new("/index.html")));
+        let outside = dir.path().join("secret.txt").canonicalize().unwrap();
+        assert!(outside.is_absolute());
+        assert_eq!(fs.resolve(&outside), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_leaving_root() {
+        let (dir, fs) = fixture();
+        let root = dir.path().join("root");
+        std::os::unix::fs::symlink(dir.path().join("secret.txt"), root.join("link.txt")).unwrap();
+        std::os::unix::fs::symlink(root.join("index.html"), root.join("inner.html")).unwrap();
+
+        assert_eq!(fs.resolve(Path::new("link.txt")), None);
+        assert!(fs.file_exists(Path::new("inner.html")));
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    buffer::Buffer,
+    filesystem::{mime, relative_path, FileSystem},
+};
+
+/// `&[(path, content)]` of files compiled into the binary, see [`embed_files!`].
+pub type EmbeddedFiles = &'static [(&'static str, &'static [u8])];
+
+/// Embed files into the binary, `"served/path" => "file/relative/to/this/source"`.
+///
+/// Expands to [`EmbeddedFiles`] for [`EmbeddedFileSystem`].
+#[macro_export]
+macro_rules! embed_files {
+    ($($path:literal => $file:expr),* $(,)?) => {
+        &[$(($path, include_bytes!($file) as &'static [u8])),*]
+    };
+}
+
+/// Embed the resources Ultralight needs (ICU data and CA certificates) from the SDK pointed to by
+/// `ULTRALIGHT_SDK_PATH` at compile time, served under `resources/`.
+#[macro_export]
+macro_rules! embed_sdk_resources {
+    () => {
+        $crate::embed_files![
+            "resources/icudt67l.dat" => concat!(env!("ULTRALIGHT_SDK_PATH"), "/resources/icudt67l.dat"),
+            "resources/cacert.pem" => concat!(env!("ULTRALIGHT_SDK_PATH"), "/resources/cacert.pem"),
+        ]
+    };
+}
+
+/// [`FileSystem`] serving files compiled into the binary, so it can ship as a single executable.
+///
+/// ```ignore
+/// let fs = EmbeddedFileSystem::new(embed_sdk_resources!())
+///     .with_files(embed_files!["app/index.html" => "../assets/index.html"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddedFileSystem {
+    files: HashMap<PathBuf, &'static [u8]>,
+}
+
+impl EmbeddedFileSystem {
+    pub fn new(files: EmbeddedFiles) -> Self {
+        Self::default().with_files(files)
+    }
+
+    /// Add more files, replacing those with the same path.
+    pub fn with_files(mut self, files: EmbeddedFiles) -> Self {
+        for &(path, data) in files {
+            if let Some(path) = relative_path(Path::new(path)) {
+                self.files.insert(path, data);
+            }
+        }
+        self
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&'static [u8]> {
+        self.files.get(&relative_path(path)?).copied()
+    }
+}
+
+impl FileSystem for EmbeddedFileSystem {
+    fn file_exists(&self, path: &Path) -> bool {
+        self.get(path).is_some()
+    }
+
+    fn get_file_mime_type(&self, path: &Path) -> String {
+        match self.get(path) {
+            Some(data) => mime::mime_type(path, Some(data)).to_owned(),
+            None => mime::UNKNOWN.to_owned(),
+        }
+    }
+
+    fn get_file_charset(&self, path: &Path) -> String {
+        match self.get(path) {
+            Some(data) => mime::charset(mime::mime_type(path, Some(data)), Some(data)),
+            None => mime::DEFAULT_CHARSET.to_owned(),
+        }
+    }
+
+    fn open_file(&self, path: &Path) -> io::Result<Buffer> {
+        let data = self.get(path).ok_or(io::ErrorKind::NotFound)?;
+        Ok(Buffer::new_static(data))
+    }
+}
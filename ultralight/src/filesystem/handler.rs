@@ -0,0 +1,343 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::{
+    buffer::Buffer,
+    filesystem::{mime, relative_path, FileSystem},
+};
+
+/// Max responses kept between `file_exists` and `open_file`, they are all dropped when reached.
+const MAX_PENDING: usize = 64;
+
+/// Content produced by a [`RequestHandler`].
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub data: Arc<[u8]>,
+    pub mime_type: String,
+    pub charset: String,
+}
+
+impl Response {
+    pub fn new(data: impl Into<Arc<[u8]>>, mime_type: &str) -> Self {
+        Self {
+            data: data.into(),
+            mime_type: mime_type.to_owned(),
+            charset: mime::DEFAULT_CHARSET.to_owned(),
+        }
+    }
+
+    /// Detect the mime-type and charset from the path and content.
+    pub fn guess(path: &Path, data: impl Into<Arc<[u8]>>) -> Self {
+        let data = data.into();
+        let mime_type = mime::mime_type(path, Some(&data));
+        let charset = mime::charset(mime_type, Some(&data));
+
+        Self {
+            mime_type: mime_type.to_owned(),
+            charset,
+            data,
+        }
+    }
+
+    pub fn with_charset(mut self, charset: &str) -> Self {
+        self.charset = charset.to_owned();
+        self
+    }
+}
+
+/// Produces content on demand, [`None`] if there is nothing at `path`.
+///
+/// Implemented for `Fn(&Path) -> Option<Response>` closures.
+pub trait RequestHandler: Send + Sync {
+    fn handle(&self, path: &Path) -> Option<Response>;
+}
+
+impl<F> RequestHandler for F
+where
+    F: Fn(&Path) -> Option<Response> + Send + Sync,
+{
+    fn handle(&self, path: &Path) -> Option<Response> {
+        self(path)
+    }
+}
+
+/// [`FileSystem`] answering the paths under a namespace with [`RequestHandler`]s.
+///
+/// Ultralight only routes `file:///` URLs through the file system, so a namespace `app` serves
+/// `file:///app/...`, the handler gets the path with the namespace stripped. Other paths go to
+/// the fallback file system, if any.
+///
+/// A handler runs on every `file_exists`, its response is kept until `open_file` of the same
+/// path. Up to 64 responses are kept, so requests that are never opened don't accumulate.
+#[derive(Default)]
+pub struct HandlerFileSystem {
+    handlers: HashMap<PathBuf, Box<dyn RequestHandler>>,
+    fallback: Option<Box<dyn FileSystem>>,
+    pending: Mutex<HashMap<PathBuf, Response>>,
+}
+
+impl HandlerFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `file:///<namespace>/...` with `handler`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `namespace` is not a single path component, eg. `app`.
+    pub fn with_handler(
+        mut self,
+        namespace: impl AsRef<Path>,
+        handler: impl RequestHandler + 'static,
+    ) -> Self {
+        let namespace = namespace.as_ref();
+        let mut components = namespace.components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) => {
+                self.handlers.insert(name.into(), Box::new(handler));
+            }
+            _ => panic!(
+                "namespace must be a single path component, got `{}`",
+                namespace.display()
+            ),
+        }
+        self
+    }
+
+    /// Serve the paths outside all namespaces.
+    pub fn with_fallback(mut self, fs: impl FileSystem + 'static) -> Self {
+        self.fallback = Some(Box::new(fs));
+        self
+    }
+}
+
+enum Route<'a> {
+    Handler(&'a dyn RequestHandler, PathBuf, PathBuf),
+    Fallback(&'a dyn FileSystem),
+    None,
+}
+
+impl HandlerFileSystem {
+    fn route(&self, path: &Path) -> Route<'_> {
+        let relative = match relative_path(path) {
+            Some(relative) => relative,
+            None => return Route::None,
+        };
+
+        // the namespace is the first component
+        let mut components = relative.components();
+        let namespace = PathBuf::from(components.next().map_or("".as_ref(), |c| c.as_os_str()));
+        if let Some(handler) = self.handlers.get(&namespace) {
+            return Route::Handler(
+                &**handler,
+                relative.clone(),
+                components.as_path().to_owned(),
+            );
+        }
+
+        match &self.fallback {
+            Some(fs) => Route::Fallback(&**fs),
+            None => Route::None,
+        }
+    }
+
+    /// Run the handler and keep its response for `key` until it is taken.
+    fn refresh(&self, handler: &dyn RequestHandler, key: PathBuf, path: &Path) -> bool {
+        let response = handler.handle(path);
+
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        match response {
+            Some(response) => {
+                if pending.len() >= MAX_PENDING && !pending.contains_key(&key) {
+                    pending.clear();
+                }
+                pending.insert(key, response);
+                true
+            }
+            None => {
+                pending.remove(&key);
+                false
+            }
+        }
+    }
+
+    /// The pending response for `key`, or a fresh one if there is none.
+    fn response(
+        &self,
+        handler: &dyn RequestHandler,
+        key: PathBuf,
+        path: &Path,
+        take: bool,
+    ) -> Option<Response> {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        let cached = match take {
+            true => pending.remove(&key),
+            false => pending.get(&key).cloned(),
+        };
+        drop(pending);
+
+        cached.or_else(|| handler.handle(path))
+    }
+}
+
+impl FileSystem for HandlerFileSystem {
+    fn file_exists(&self, path: &Path) -> bool {
+        match self.route(path) {
+            Route::Handler(handler, key, path) => self.refresh(handler, key, &path),
+            Route::Fallback(fs) => fs.file_exists(path),
+            Route::None => false,
+        }
+    }
+
+    fn get_file_mime_type(&self, path: &Path) -> String {
+        let response = match self.route(path) {
+            Route::Handler(handler, key, path) => self.response(handler, key, &path, false),
+            Route::Fallback(fs) => return fs.get_file_mime_type(path),
+            Route::None => None,
+        };
+        response.map_or_else(|| mime::UNKNOWN.to_owned(), |r| r.mime_type)
+    }
+
+    fn get_file_charset(&self, path: &Path) -> String {
+        let response = match self.route(path) {
+            Route::Handler(handler, key, path) => self.response(handler, key, &path, false),
+            Route::Fallback(fs) => return fs.get_file_charset(path),
+            Route::None => None,
+        };
+        response.map_or_else(|| mime::DEFAULT_CHARSET.to_owned(), |r| r.charset)
+    }
+
+    fn open_file(&self, path: &Path) -> io::Result<Buffer> {
+        let response = match self.route(path) {
+            Route::Handler(handler, key, path) => self.response(handler, key, &path, true),
+            Route::Fallback(fs) => return fs.open_file(path),
+            Route::None => None,
+        };
+        let response = response.ok_or(io::ErrorKind::NotFound)?;
+        Ok(Buffer::new_shared(response.data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::filesystem::MemoryFileSystem;
+
+    fn echo(path: &Path) -> Option<Response> {
+        match path.to_str()? {
+            "missing" => None,
+            path => Some(Response::new(path.as_bytes().to_vec(), "text/plain")),
+        }
+    }
+
+    fn pending(fs: &HandlerFileSystem) -> usize {
+        fs.pending.lock().unwrap().len()
+    }
+
+    #[test]
+    fn routes_namespace_to_handler() {
+        let fallback = MemoryFileSystem::new();
+        fallback.insert_with_mime_type("other/page.html", b"fallback".to_vec(), "text/html");
+        let fs = HandlerFileSystem::new()
+            .with_handler("app", echo)
+            .with_fallback(fallback);
+
+        assert!(fs.file_exists(Path::new("/app/data/item.json")));
+        assert_eq!(fs.get_file_mime_type(Path::new("app/x")), "text/plain");
+        assert!(!fs.file_exists(Path::new("app/missing")));
+
+        assert!(fs.file_exists(Path::new("other/page.html")));
+        assert_eq!(
+            fs.get_file_mime_type(Path::new("other/page.html")),
+            "text/html"
+        );
+        assert!(!fs.file_exists(Path::new("apps/x")));
+        assert!(!fs.file_exists(Path::new("app/../other/page.html")));
+    }
+
+    #[test]
+    fn handler_gets_path_without_namespace() {
+        let fs = HandlerFileSystem::new().with_handler("app", echo);
+
+        assert!(fs.file_exists(Path::new("app/a/b.txt")));
+        let pending = fs.pending.lock().unwrap();
+        assert_eq!(&*pending[Path::new("app/a/b.txt")].data, b"a/b.txt");
+    }
+
+    #[test]
+    fn without_fallback_other_paths_are_missing() {
+        let fs = HandlerFileSystem::new().with_handler("app", echo);
+
+        assert!(!fs.file_exists(Path::new("index.html")));
+        assert_eq!(
+            fs.get_file_mime_type(Path::new("index.html")),
+            mime::UNKNOWN
+        );
+    }
+
+    #[test]
+    fn file_exists_runs_handler_again() {
+        let calls = AtomicUsize::new(0);
+        let fs = HandlerFileSystem::new().with_handler("app", move |path: &Path| {
+            let n = calls.fetch_add(1, Ordering::Relaxed);
+            Some(Response::new(
+                format!("{}:{n}", path.display()).into_bytes(),
+                "text/plain",
+            ))
+        });
+
+        assert!(fs.file_exists(Path::new("app/a")));
+        assert!(fs.file_exists(Path::new("app/a")));
+
+        let pending = fs.pending.lock().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(&*pending[Path::new("app/a")].data, b"a:1");
+    }
+
+    #[test]
+    fn pending_responses_are_bounded() {
+        let fs = HandlerFileSystem::new().with_handler("app", echo);
+
+        for i in 0..MAX_PENDING {
+            assert!(fs.file_exists(&Path::new("app").join(i.to_string())));
+        }
+        assert_eq!(pending(&fs), MAX_PENDING);
+
+        // refreshing a kept response doesn't evict
+        assert!(fs.file_exists(Path::new("app/0")));
+        assert_eq!(pending(&fs), MAX_PENDING);
+
+        assert!(fs.file_exists(Path::new("app/new")));
+        assert_eq!(pending(&fs), 1);
+    }
+
+    #[test]
+    fn missing_response_is_dropped() {
+        let fs = HandlerFileSystem::new().with_handler("app", |path: &Path| {
+            (!path.starts_with("gone")).then(|| Response::new(b"x".to_vec(), "text/plain"))
+        });
+
+        assert!(fs.file_exists(Path::new("app/kept")));
+        assert!(!fs.file_exists(Path::new("app/gone")));
+        assert_eq!(pending(&fs), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "single path component")]
+    fn nested_namespace_panics() {
+        let _ = HandlerFileSystem::new().with_handler("app/api", echo);
+    }
+
+    #[test]
+    #[should_panic(expected = "single path component")]
+    fn parent_namespace_panics() {
+        let _ = HandlerFileSystem::new().with_handler("..", echo);
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    buffer::Buffer,
+    filesystem::{mime, relative_path, FileSystem},
+};
+
+/// Stack of [`FileSystem`]s, the first one having the file serves it.
+///
+/// Put overrides first: `LayeredFileSystem::new().with_layer(app).with_layer(shared)`.
+#[derive(Default)]
+pub struct LayeredFileSystem {
+    layers: Vec<Box<dyn FileSystem>>,
+}
+
+impl LayeredFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a layer below the existing ones.
+    pub fn with_layer(mut self, fs: impl FileSystem + 'static) -> Self {
+        self.layers.push(Box::new(fs));
+        self
+    }
+
+    fn find(&self, path: &Path) -> Option<&dyn FileSystem> {
+        self.layers
+            .iter()
+            .map(|fs| &**fs)
+            .find(|fs| fs.file_exists(path))
+    }
+}
+
+impl FileSystem for LayeredFileSystem {
+    fn file_exists(&self, path: &Path) -> bool {
+        self.find(path).is_some()
+    }
+
+    fn get_file_mime_type(&self, path: &Path) -> String {
+        match self.find(path) {
+            Some(fs) => fs.get_file_mime_type(path),
+            None => mime::UNKNOWN.to_owned(),
+        }
+    }
+
+    fn get_file_charset(&self, path: &Path) -> String {
+        match self.find(path) {
+            Some(fs) => fs.get_file_charset(path),
+            None => mime::DEFAULT_CHARSET.to_owned(),
+        }
+    }
+
+    fn open_file(&self, path: &Path) -> io::Result<Buffer> {
+        match self.find(path) {
+            Some(fs) => fs.open_file(path),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+}
+
+/// Routes path prefixes to different [`FileSystem`]s, the longest matching prefix wins.
+///
+/// The prefix is stripped: with `assets` mounted, `assets/app.css` is asked to the mounted file
+/// system as `app.css`. Mount at `""` to serve everything else.
+#[derive(Default)]
+pub struct MountFileSystem {
+    // sorted by decreasing prefix length
+    mounts: Vec<(PathBuf, Box<dyn FileSystem>)>,
+}
+
+impl MountFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mount `fs` at `prefix`, replacing a file system already mounted there.
+    pub fn with_mount(mut self, prefix: impl AsRef<Path>, fs: impl FileSystem + 'static) -> Self {
+        let prefix = match relative_path(prefix.as_ref()) {
+            Some(prefix) => prefix,
+            None => return self,
+        };
+
+        self.mounts.retain(|(p, _)| *p != prefix);
+        self.mounts.push((prefix, Box::new(fs)));
+        self.mounts
+            .sort_by_key(|(p, _)| std::cmp::Reverse(p.components().count()));
+        self
+    }
+
+    fn route(&self, path: &Path) -> Option<(&dyn FileSystem, PathBuf)> {
+        let path = relative_path(path)?;
+        self.mounts.iter().find_map(|(prefix, fs)| {
+            let rest = path.strip_prefix(prefix).ok()?;
+            Some((&**fs, rest.to_owned()))
+        })
+    }
+}
+
+impl FileSystem for MountFileSystem {
+    fn file_exists(&self, path: &Path) -> bool {
+        self.route(path)
+            .is_some_and(|(fs, path)| fs.file_exists(&path))
+    }
+
+    fn get_file_mime_type(&self, path: &Path) -> String {
+        match self.route(path) {
+            Some((fs, path)) => fs.get_file_mime_type(&path),
+            None => mime::UNKNOWN.to_owned(),
+        }
+    }
+
+    fn get_file_charset(&self, path: &Path) -> String {
+        match self.route(path) {
+            Some((fs, path)) => fs.get_file_charset(&path),
+            None => mime::DEFAULT_CHARSET.to_owned(),
+        }
+    }
+
+    fn open_file(&self, path: &Path) -> io::Result<Buffer> {
+        match self.route(path) {
+            Some((fs, path)) => fs.open_file(&path),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::MemoryFileSystem;
+
+    /// A file system whose files all have `mime_type`, to tell which one served a path.
+    fn tagged(mime_type: &str, paths: &[&str]) -> MemoryFileSystem {
+        let fs = MemoryFileSystem::new();
+        for path in paths {
+            fs.insert_with_mime_type(path, b"x".to_vec(), mime_type);
+        }
+        fs
+    }
+
+    #[test]
+    fn first_layer_wins() {
+        let fs = LayeredFileSystem::new()
+            .with_layer(tagged("text/top", &["index.html"]))
+            .with_layer(tagged("text/bottom", &["index.html", "app.css"]));
+
+        assert_eq!(fs.get_file_mime_type(Path::new("index.html")), "text/top");
+        assert_eq!(fs.get_file_mime_type(Path::new("app.css")), "text/bottom");
+        assert!(!fs.file_exists(Path::new("missing.js")));
+        assert_eq!(
+            fs.get_file_mime_type(Path::new("missing.js")),
+            mime::UNKNOWN
+        );
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let fs = MountFileSystem::new()
+            .with_mount(
+                "assets",
+                tagged("text/assets", &["img/logo.png", "img/other.png", "app.css"]),
+            )
+            .with_mount("assets/img", tagged("text/img", &["logo.png"]));
+
+        assert_eq!(
+            fs.get_file_mime_type(Path::new("assets/img/logo.png")),
+            "text/img"
+        );
+        assert_eq!(
+            fs.get_file_mime_type(Path::new("assets/app.css")),
+            "text/assets"
+        );
+        // the longer mount shadows the shorter one even when it lacks the file
+        assert!(!fs.file_exists(Path::new("assets/img/other.png")));
+    }
+
+    #[test]
+    fn mount_strips_prefix_and_root_catches_rest() {
+        let fs = MountFileSystem::new()
+            .with_mount("", tagged("text/root", &["index.html"]))
+            .with_mount("assets", tagged("text/assets", &["app.css"]));
+
+        assert!(fs.file_exists(Path::new("assets/app.css")));
+        assert!(!fs.file_exists(Path::new("app.css")));
+        assert_eq!(fs.get_file_mime_type(Path::new("index.html")), "text/root");
+        assert!(!fs.file_exists(Path::new("assets/index.html")));
+    }
+
+    #[test]
+    fn prefix_matches_whole_components() {
+        let fs = MountFileSystem::new().with_mount("app", tagged("text/app", &["x"]));
+
+        assert!(fs.file_exists(Path::new("app/x")));
+        assert!(!fs.file_exists(Path::new("apps/x")));
+        assert!(!fs.file_exists(Path::new("app/../app/x")));
+    }
+
+    #[test]
+    fn remount_replaces() {
+        let fs = MountFileSystem::new()
+            .with_mount("app", tagged("text/old", &["x"]))
+            .with_mount("app", tagged("text/new", &["x"]));
+
+        assert_eq!(fs.get_file_mime_type(Path::new("app/x")), "text/new");
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::{
+    buffer::Buffer,
+    filesystem::{mime, relative_path, FileSystem},
+};
+
+struct Entry {
+    data: Arc<[u8]>,
+    mime_type: Option<String>,
+}
+
+/// [`FileSystem`] serving files from memory.
+///
+/// Clones share the same files, keep one to insert, replace and remove files while the renderer
+/// is running. Files are handed to Ultralight without copying.
+#[derive(Clone, Default)]
+pub struct MemoryFileSystem {
+    files: Arc<RwLock<HashMap<PathBuf, Entry>>>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a file, the mime-type is detected from the path and content.
+    ///
+    /// Paths with `..` are ignored.
+    pub fn insert(&self, path: impl AsRef<Path>, data: impl Into<Arc<[u8]>>) {
+        self.insert_entry(path.as_ref(), data.into(), None);
+    }
+
+    pub fn insert_with_mime_type(
+        &self,
+        path: impl AsRef<Path>,
+        data: impl Into<Arc<[u8]>>,
+        mime_type: &str,
+    ) {
+        self.insert_entry(path.as_ref(), data.into(), Some(mime_type.to_owned()));
+    }
+
+    /// Remove a file, returns its content. Buffers already handed out stay valid.
+    pub fn remove(&self, path: impl AsRef<Path>) -> Option<Arc<[u8]>> {
+        let path = relative_path(path.as_ref())?;
+        self.write().remove(&path).map(|entry| entry.data)
+    }
+
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<Arc<[u8]>> {
+        let path = relative_path(path.as_ref())?;
+        self.read().get(&path).map(|entry| entry.data.clone())
+    }
+
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        self.get(path).is_some()
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.read().keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.write().clear();
+    }
+}
+
+impl MemoryFileSystem {
+    fn insert_entry(&self, path: &Path, data: Arc<[u8]>, mime_type: Option<String>) {
+        if let Some(path) = relative_path(path) {
+            self.write().insert(path, Entry { data, mime_type });
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<PathBuf, Entry>> {
+        self.files.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<PathBuf, Entry>> {
+        self.files.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn mime_type(&self, path: &Path) -> Option<String> {
+        let path = relative_path(path)?;
+        let files = self.read();
+        let entry = files.get(&path)?;
+
+        Some(match &entry.mime_type {
+            Some(mime_type) => mime_type.clone(),
+            None => mime::mime_type(&path, Some(&entry.data)).to_owned(),
+        })
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn file_exists(&self, path: &Path) -> bool {
+        self.contains(path)
+    }
+
+    fn get_file_mime_type(&self, path: &Path) -> String {
+        self.mime_type(path)
+            .unwrap_or_else(|| mime::UNKNOWN.to_owned())
+    }
+
+    fn get_file_charset(&self, path: &Path) -> String {
+        match (self.mime_type(path), self.get(path)) {
+            (Some(mime_type), Some(data)) => mime::charset(&mime_type, Some(&data)),
+            _ => mime::DEFAULT_CHARSET.to_owned(),
+        }
+    }
+
+    fn open_file(&self, path: &Path) -> io::Result<Buffer> {
+        let data = self.get(path).ok_or(io::ErrorKind::NotFound)?;
+        Ok(Buffer::new_shared(data))
+    }
+}
@@ -0,0 +1,312 @@
+//! MIME type and charset detection for [`FileSystem`](crate::filesystem::FileSystem)
+//! implementations.
+//!
+//! [`mime_type`] and [`charset`] take the path and, when available, the first bytes of the file
+//! ([`SNIFF_LEN`] is enough).
+
+use std::path::Path;
+
+/// Fallback mime-type expected by Ultralight.
+pub const UNKNOWN: &str = "application/unknown";
+
+/// Fallback charset, also used for all non text files.
+pub const DEFAULT_CHARSET: &str = "utf-8";
+
+/// Number of leading bytes looked at by the sniffing functions.
+pub const SNIFF_LEN: usize = 1024;
+
+/// Mime-type by extension, falls back to sniffing `head` and then [`UNKNOWN`].
+pub fn mime_type(path: &Path, head: Option<&[u8]>) -> &'static str {
+    from_extension(path)
+        .or_else(|| head.and_then(sniff))
+        .unwrap_or(UNKNOWN)
+}
+
+/// Charset of a file of type `mime_type`, from its BOM, `<meta charset>` or `@charset`.
+pub fn charset(mime_type: &str, head: Option<&[u8]>) -> String {
+    if !is_text(mime_type) {
+        return DEFAULT_CHARSET.to_owned();
+    }
+
+    let head = match head {
+        Some(head) => &head[..head.len().min(SNIFF_LEN)],
+        None => return DEFAULT_CHARSET.to_owned(),
+    };
+
+    if let Some(charset) = charset_from_bom(head) {
+        return charset.to_owned();
+    }
+
+    let declared = match mime_type {
+        "text/html" | "image/svg+xml" | "text/xml" => declared_markup_charset(head),
+        "text/css" => declared_css_charset(head),
+        _ => None,
+    };
+    declared.unwrap_or_else(|| DEFAULT_CHARSET.to_owned())
+}
+
+pub fn from_extension(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+    let mime_type = match extension.as_str() {
+        "html" | "htm" | "xhtml" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" | "cjs" => "text/javascript",
+        "json" | "map" => "application/json",
+        "txt" => "text/plain",
+        "xml" => "text/xml",
+        "csv" => "text/csv",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "avif" => "image/avif",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "dat" => "application/octet-stream",
+        "pem" => "application/x-pem-file",
+        _ => return None,
+    };
+    Some(mime_type)
+}
+
+/// Mime-type from the magic bytes, or markup at the start of text files.
+pub fn sniff(head: &[u8]) -> Option<&'static str> {
+    const MAGIC: [(&[u8], &str); 10] = [
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"wOFF", "font/woff"),
+        (b"wOF2", "font/woff2"),
+        (b"\x00\x01\x00\x00", "font/ttf"),
+        (b"OTTO", "font/otf"),
+        (b"\x00asm", "application/wasm"),
+        (b"%PDF-", "application/pdf"),
+    ];
+
+    if let Some((_, mime_type)) = MAGIC.iter().find(|(magic, _)| head.starts_with(magic)) {
+        return Some(mime_type);
+    }
+    if head.len() >= 12 && &head[..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+
+    let text = without_bom(head);
+    let start = text
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .map_or(&[][..], |i| &text[i..]);
+    let lower = start[..start.len().min(SNIFF_LEN)].to_ascii_lowercase();
+
+    if lower.starts_with(b"<!doctype html") || lower.starts_with(b"<html") {
+        Some("text/html")
+    } else if lower.starts_with(b"<svg")
+        || (lower.starts_with(b"<?xml") && contains(&lower, b"<svg"))
+    {
+        Some("image/svg+xml")
+    } else if lower.starts_with(b"<?xml") {
+        Some("text/xml")
+    } else {
+        None
+    }
+}
+
+/// Whether the charset matters for files of this mime-type.
+pub fn is_text(mime_type: &str) -> bool {
+    mime_type.starts_with("text/") || matches!(mime_type, "application/json" | "image/svg+xml")
+}
+
+pub fn charset_from_bom(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"\xef\xbb\xbf") {
+        Some("utf-8")
+    } else if head.starts_with(b"\xff\xfe") {
+        Some("utf-16le")
+    } else if head.starts_with(b"\xfe\xff") {
+        Some("utf-16be")
+    } else {
+        None
+    }
+}
+
+fn without_bom(head: &[u8]) -> &[u8] {
+    head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    find(haystack, needle).is_some()
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// `<meta charset="...">`, `<meta http-equiv content="...; charset=...">` or
+/// `<?xml encoding="..."?>`.
+fn declared_markup_charset(head: &[u8]) -> Option<String> {
+    let lower = head.to_ascii_lowercase();
+
+    if lower.starts_with(b"<?xml") {
+        let end = find(&lower, b"?>")?;
+        let at = find(&lower[..end], b"encoding=")? + "encoding=".len();
+        return charset_value(&lower[at..end]);
+    }
+
+    let mut rest = &lower[..];
+    while let Some(at) = find(rest, b"<meta") {
+        rest = &rest[at..];
+        let end = rest.iter().position(|&b| b == b'>').unwrap_or(rest.len());
+        let tag = &rest[..end];
+
+        if let Some(at) = find(tag, b"charset=") {
+            return charset_value(&tag[at + "charset=".len()..]);
+        }
+        rest = &rest[end..];
+    }
+
+    None
+}
+
+/// `@charset "...";`, only valid as the very first bytes of the file.
+fn declared_css_charset(head: &[u8]) -> Option<String> {
+    let rest = without_bom(head).strip_prefix(b"@charset \"")?;
+    let end = rest.iter().position(|&b| b == b'"')?;
+    charset_value(&rest[..end]).map(|c| c.to_ascii_lowercase())
+}
+
+fn charset_value(value: &[u8]) -> Option<String> {
+    let value = value
+        .iter()
+        .skip_while(|&&b| b == b'"' || b == b'\'' || b.is_ascii_whitespace())
+        .take_while(|&&b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b':' | b'.'))
+        .copied()
+        .collect::<Vec<_>>();
+
+    match value.is_empty() {
+        true => None,
+        false => String::from_utf8(value).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_extension_is_case_insensitive() {
+        assert_eq!(from_extension(Path::new("index.HTML")), Some("text/html"));
+        assert_eq!(
+            from_extension(Path::new("a/b/app.mjs")),
+            Some("text/javascript")
+        );
+        assert_eq!(from_extension(Path::new("font.woff2")), Some("font/woff2"));
+    }
+
+    #[test]
+    fn from_extension_unknown() {
+        assert_eq!(from_extension(Path::new("README")), None);
+        assert_eq!(from_extension(Path::new("archive.xyz")), None);
+    }
+
+    #[test]
+    fn sniff_magic_bytes() {
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\0\0"), Some("image/png"));
+        assert_eq!(sniff(b"\xff\xd8\xff\xe0"), Some("image/jpeg"));
+        assert_eq!(sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff(b"\x00\x01\x00\x00\x00"), Some("font/ttf"));
+    }
+
+    #[test]
+    fn sniff_markup() {
+        assert_eq!(sniff(b"\xef\xbb\xbf  <!DOCTYPE html>"), Some("text/html"));
+        assert_eq!(
+            sniff(b"<?xml version=\"1.0\"?>\n<svg xmlns=\"\">"),
+            Some("image/svg+xml")
+        );
+        assert_eq!(sniff(b"<?xml version=\"1.0\"?><feed>"), Some("text/xml"));
+    }
+
+    #[test]
+    fn sniff_plain_text() {
+        assert_eq!(sniff(b"true"), None);
+        assert_eq!(sniff(b"hello"), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    #[test]
+    fn charset_from_bom_wins() {
+        assert_eq!(charset_from_bom(b"\xef\xbb\xbfhi"), Some("utf-8"));
+        assert_eq!(charset_from_bom(b"\xff\xfeh\0"), Some("utf-16le"));
+        assert_eq!(charset_from_bom(b"\xfe\xff\0h"), Some("utf-16be"));
+        assert_eq!(charset_from_bom(b"hi"), None);
+
+        let html = b"\xff\xfe<meta charset=\"iso-8859-1\">";
+        assert_eq!(charset("text/html", Some(html)), "utf-16le");
+    }
+
+    #[test]
+    fn charset_from_meta() {
+        let head = b"<html><head><meta name=\"x\"><META CHARSET=\"Shift_JIS\">";
+        assert_eq!(charset("text/html", Some(head)), "shift_jis");
+        assert_eq!(
+            charset("text/html", Some(b"<meta charset=windows-1252>")),
+            "windows-1252"
+        );
+    }
+
+    #[test]
+    fn charset_from_http_equiv() {
+        let head = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=ISO-8859-1\">";
+        assert_eq!(charset("text/html", Some(head)), "iso-8859-1");
+    }
+
+    #[test]
+    fn charset_from_xml_declaration() {
+        let head = b"<?xml version=\"1.0\" encoding='EUC-JP'?><svg>";
+        assert_eq!(charset("image/svg+xml", Some(head)), "euc-jp");
+        assert_eq!(
+            charset("text/xml", Some(b"<?xml version=\"1.0\"?><feed>")),
+            DEFAULT_CHARSET
+        );
+    }
+
+    #[test]
+    fn charset_from_css_rule() {
+        assert_eq!(
+            charset("text/css", Some(b"@charset \"ISO-8859-15\";\nbody {}")),
+            "iso-8859-15"
+        );
+        // only valid as the very first bytes
+        assert_eq!(
+            charset("text/css", Some(b"\n@charset \"ISO-8859-15\";")),
+            DEFAULT_CHARSET
+        );
+    }
+
+    #[test]
+    fn charset_defaults() {
+        let meta = b"<meta charset=\"iso-8859-1\">";
+        assert_eq!(charset("image/png", Some(meta)), DEFAULT_CHARSET);
+        assert_eq!(charset("text/html", None), DEFAULT_CHARSET);
+        assert_eq!(charset("text/html", Some(b"<p>hi</p>")), DEFAULT_CHARSET);
+        // declarations are only looked for in markup and css
+        assert_eq!(charset("text/plain", Some(meta)), DEFAULT_CHARSET);
+    }
+
+    #[test]
+    fn charset_value_trims_quotes() {
+        assert_eq!(charset_value(b"\"utf-8\">"), Some("utf-8".to_owned()));
+        assert_eq!(charset_value(b" 'koi8-r';"), Some("koi8-r".to_owned()));
+        assert_eq!(charset_value(b"\"\""), None);
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    io,
+    mem::ManuallyDrop,
+    ops::Deref,
+    panic::{self, AssertUnwindSafe},
+    path::{Component, Path, PathBuf},
+    ptr::null_mut,
+    sync::{PoisonError, RwLock},
+};
+
+use ultralight_sys::*;
+
+use crate::{buffer::Buffer, string::UString};
+
+#[cfg(feature = "archive")]
+mod archive;
+mod audit;
+mod dir;
+mod embedded;
+mod handler;
+mod layered;
+mod memory;
+pub mod mime;
+
+#[cfg(feature = "archive")]
+pub use archive::ArchiveFileSystem;
+pub use audit::{AccessKind, AccessRecord, AuditFileSystem, AuditLog};
+pub use dir::DirFileSystem;
+pub use embedded::{EmbeddedFileSystem, EmbeddedFiles};
+pub use handler::{HandlerFileSystem, RequestHandler, Response};
+pub use layered::{LayeredFileSystem, MountFileSystem};
+pub use memory::MemoryFileSystem;
+
+static USER_FILE_SYSTEM: RwLock<Option<Box<dyn FileSystem>>> = RwLock::new(None);
+static FILE_SYSTEM: ULFileSystem = ULFileSystem {
+    file_exists: Some(file_exists_callback),
+    get_file_mime_type: Some(get_file_mime_type_callback),
+    get_file_charset: Some(get_file_charset_callback),
+    open_file: Some(open_file_callback),
+};
+
+/// Run `f` with the user file system and the path given by Ultralight.
+///
+/// Unwinding out of the callbacks is undefined behaviour, a panic returns `fallback` instead. A
+/// poisoned lock still holds a valid file system and a missing one falls back too.
+unsafe fn with_file_system<T>(
+    path: ULString,
+    fallback: impl Fn() -> T,
+    f: impl FnOnce(&dyn FileSystem, &Path) -> T,
+) -> T {
+    let path = ManuallyDrop::new(UString::from_raw(path));
+    let path = Path::new(path.deref().deref());
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let guard = USER_FILE_SYSTEM
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        guard.as_ref().map(|file_system| f(&**file_system, path))
+    }));
+
+    match result {
+        Ok(Some(value)) => value,
+        Ok(None) | Err(_) => fallback(),
+    }
+}
+
+unsafe extern "C" fn file_exists_callback(path: ULString) -> bool {
+    with_file_system(path, || false, |fs, path| fs.file_exists(path))
+}
+
+unsafe extern "C" fn get_file_mime_type_callback(path: ULString) -> ULString {
+    let mime_type = with_file_system(
+        path,
+        || mime::UNKNOWN.to_owned(),
+        |fs, path| fs.get_file_mime_type(path),
+    );
+    UString::from(mime_type).into_raw()
+}
+
+unsafe extern "C" fn get_file_charset_callback(path: ULString) -> ULString {
+    let charset = with_file_system(
+        path,
+        || mime::DEFAULT_CHARSET.to_owned(),
+        |fs, path| fs.get_file_charset(path),
+    );
+    UString::from(charset).into_raw()
+}
+
+unsafe extern "C" fn open_file_callback(path: ULString) -> ULBuffer {
+    let buffer = with_file_system(path, || None, |fs, path| fs.open_file(path).ok());
+    buffer.map_or(null_mut(), Buffer::into_raw)
+}
+
+pub trait FileSystem: Send + Sync {
+    /// The callback invoked when the FileSystem wants to check if a file path exists,
+    /// return [`true`] if it exists.
+    fn file_exists(&self, path: &Path) -> bool;
+
+    /// Get the mime-type of the file (eg "text/html").
+    ///
+    /// This is usually determined by analyzing the file extension.
+    ///
+    /// If a mime-type cannot be determined, you should return "application/unknown" for this value.
+    ///
+    /// The library will consume the result and call ulDestroyString() after this call returns.
+    fn get_file_mime_type(&self, path: &Path) -> String;
+
+    ///  Get the charset / encoding of the file (eg "utf-8").
+    ///
+    /// This is only important for text-based files and is usually determined by analyzing the
+    /// contents of the file.
+    ///
+    /// If a charset cannot be determined, it's usually safe to return "utf-8" for this value.
+    ///
+    /// The library will consume the result and call ulDestroyString() after this call returns.
+    fn get_file_charset(&self, path: &Path) -> String;
+
+    /// Open file for reading and map it to a Buffer.
+    ///
+    /// To minimize copies, you should map the requested file into memory and use ulCreateBuffer()
+    /// to wrap the data pointer (unmapping should be performed in the destruction callback).
+    ///
+    /// If the file was unable to be opened, return an error, Ultralight gets NULL.
+    fn open_file(&self, path: &Path) -> io::Result<Buffer>;
+}
+
+impl<F: FileSystem + ?Sized> FileSystem for Box<F> {
+    fn file_exists(&self, path: &Path) -> bool {
+        (**self).file_exists(path)
+    }
+
+    fn get_file_mime_type(&self, path: &Path) -> String {
+        (**self).get_file_mime_type(path)
+    }
+
+    fn get_file_charset(&self, path: &Path) -> String {
+        (**self).get_file_charset(path)
+    }
+
+    fn open_file(&self, path: &Path) -> io::Result<Buffer> {
+        (**self).open_file(path)
+    }
+}
+
+pub(crate) fn set_platform_file_system(fs: Box<dyn FileSystem>) {
+    // replacing the file system is the recovery from a poisoned lock
+    *USER_FILE_SYSTEM
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(fs);
+
+    unsafe {
+        ulPlatformSetFileSystem(FILE_SYSTEM);
+    }
+}
+
+/// `path` as a plain relative path, [`None`] if it contains `..`.
+///
+/// `/foo` and `C:\foo` are treated as relative too.
+pub(crate) fn relative_path(path: &Path) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::RootDir | Component::Prefix(_) | Component::CurDir => {}
+            Component::ParentDir => return None,
+        }
+    }
+
+    Some(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_path_strips_root_and_cur_dir() {
+        let expected = Some(PathBuf::from("a/b.html"));
+        assert_eq!(relative_path(Path::new("a/b.html")), expected);
+        assert_eq!(relative_path(Path::new("/a/b.html")), expected);
+        assert_eq!(relative_path(Path::new("./a/./b.html")), expected);
+    }
+
+    #[test]
+    fn relative_path_rejects_parent_dir() {
+        assert_eq!(relative_path(Path::new("../a")), None);
+        assert_eq!(relative_path(Path::new("a/../b")), None);
+        assert_eq!(relative_path(Path::new("/a/../../etc/passwd")), None);
+    }
+}
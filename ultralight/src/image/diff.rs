@@ -0,0 +1,352 @@
+use crate::image::RgbaImage;
+
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Max per-channel difference for two pixels to be considered equal.
+    ///
+    /// (Default = 0)
+    pub channel_threshold: u8,
+
+    /// Don't count pixels that look like anti-aliasing (detected like pixelmatch does) as
+    /// differences, they are reported separately in [`DiffStats::anti_aliased_pixels`].
+    ///
+    /// (Default = [`true`])
+    pub detect_anti_aliasing: bool,
+
+    /// Color of differing pixels in the diff image.
+    ///
+    /// (Default = red)
+    pub diff_color: [u8; 4],
+
+    /// Color of anti-aliased pixels in the diff image.
+    ///
+    /// (Default = yellow)
+    pub anti_aliased_color: [u8; 4],
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            channel_threshold: 0,
+            detect_anti_aliasing: true,
+            diff_color: [255, 0, 0, 255],
+            anti_aliased_color: [255, 255, 0, 255],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffStats {
+    pub total_pixels: usize,
+    pub differing_pixels: usize,
+    pub anti_aliased_pixels: usize,
+    /// Largest per-channel difference over all pixels.
+    pub max_channel_delta: u8,
+    /// Mean structural similarity of the luma, 1.0 for identical images.
+    pub ssim: f64,
+}
+
+impl DiffStats {
+    /// Ratio of differing pixels, in `0.0..=1.0`.
+    pub fn differing_ratio(&self) -> f64 {
+        if self.total_pixels == 0 {
+            return 0.0;
+        }
+        self.differing_pixels as f64 / self.total_pixels as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageDiff {
+    pub stats: DiffStats,
+    /// Faded grayscale of `expected` with differing and anti-aliased pixels highlighted.
+    pub image: RgbaImage,
+}
+
+/// Compare two images pixel by pixel.
+///
+/// # Panics
+///
+/// Panics if the images don't have the same size.
+pub fn compare(expected: &RgbaImage, actual: &RgbaImage, options: &DiffOptions) -> ImageDiff {
+    assert_size(expected, actual);
+
+    let (width, height) = (expected.width(), expected.height());
+    let mut image = RgbaImage::new(width, height);
+    let mut stats = DiffStats {
+        total_pixels: width as usize * height as usize,
+        differing_pixels: 0,
+        anti_aliased_pixels: 0,
+        max_channel_delta: 0,
+        ssim: ssim(expected, actual),
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let (e, a) = (expected.pixel(x, y), actual.pixel(x, y));
+            let delta = e
+                .iter()
+                .zip(a)
+                .map(|(e, a)| e.abs_diff(a))
+                .max()
+                .unwrap_or(0);
+            stats.max_channel_delta = stats.max_channel_delta.max(delta);
+
+            let color = if delta <= options.channel_threshold {
+                let luma = luma(blend_white(e)).round() as u8;
+                let faded = 255 - (255 - luma) / 4;
+                [faded, faded, faded, 255]
+            } else if options.detect_anti_aliasing
+                && (anti_aliased(expected, actual, x, y) || anti_aliased(actual, expected, x, y))
+            {
+                stats.anti_aliased_pixels += 1;
+                options.anti_aliased_color
+            } else {
+                stats.differing_pixels += 1;
+                options.diff_color
+            };
+            image.set_pixel(x, y, color);
+        }
+    }
+
+    ImageDiff { stats, image }
+}
+
+/// Mean SSIM over 8x8 luma windows (stride 4), in `-1.0..=1.0`.
+///
+/// # Panics
+///
+/// Panics if the images don't have the same size.
+pub fn ssim(a: &RgbaImage, b: &RgbaImage) -> f64 {
+    const WINDOW: u32 = 8;
+    const STRIDE: u32 = 4;
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    assert_size(a, b);
+
+    let (width, height) = (a.width(), a.height());
+    if width == 0 || height == 0 {
+        return 1.0;
+    }
+
+    let (window_width, window_height) = (WINDOW.min(width), WINDOW.min(height));
+    let (mut total, mut windows) = (0.0, 0usize);
+
+    let mut y0 = 0;
+    while y0 + window_height <= height {
+        let mut x0 = 0;
+        while x0 + window_width <= width {
+            let n = f64::from(window_width * window_height);
+            let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab) =
+                (0.0, 0.0, 0.0, 0.0, 0.0);
+            for y in y0..y0 + window_height {
+                for x in x0..x0 + window_width {
+                    let la = luma(blend_white(a.pixel(x, y)));
+                    let lb = luma(blend_white(b.pixel(x, y)));
+                    sum_a += la;
+                    sum_b += lb;
+                    sum_aa += la * la;
+                    sum_bb += lb * lb;
+                    sum_ab += la * lb;
+                }
+            }
+
+            let (mean_a, mean_b) = (sum_a / n, sum_b / n);
+            let var_a = sum_aa / n - mean_a * mean_a;
+            let var_b = sum_bb / n - mean_b * mean_b;
+            let covar = sum_ab / n - mean_a * mean_b;
+
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covar + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+            windows += 1;
+
+            x0 += STRIDE;
+        }
+        y0 += STRIDE;
+    }
+
+    total / windows as f64
+}
+
+fn assert_size(a: &RgbaImage, b: &RgbaImage) {
+    assert!(
+        a.width() == b.width() && a.height() == b.height(),
+        "image size mismatch, {}x{} vs {}x{}",
+        a.width(),
+        a.height(),
+        b.width(),
+        b.height()
+    );
+}
+
+/// Composite a RGBA pixel over white, so transparency is comparable.
+fn blend_white([r, g, b, a]: [u8; 4]) -> [f64; 3] {
+    let alpha = f64::from(a) / 255.0;
+    [r, g, b].map(|c| 255.0 + (f64::from(c) - 255.0) * alpha)
+}
+
+fn luma([r, g, b]: [f64; 3]) -> f64 {
+    r * 0.29889531 + g * 0.58662247 + b * 0.11448223
+}
+
+/// Neighbours of `(x, y)` in the 3x3 block, and 1 if the pixel sits on the image edge (pixelmatch
+/// counts the edge as one matching neighbour).
+fn neighbours(image: &RgbaImage, x: u32, y: u32) -> (impl Iterator<Item = (u32, u32)>, usize) {
+    let (x0, y0) = (x.saturating_sub(1), y.saturating_sub(1));
+    let (x1, y1) = (
+        (x + 1).min(image.width() - 1),
+        (y + 1).min(image.height() - 1),
+    );
+    let on_edge = x == x0 || x == x1 || y == y0 || y == y1;
+
+    let iter = (y0..=y1)
+        .flat_map(move |ny| (x0..=x1).map(move |nx| (nx, ny)))
+        .filter(move |&p| p != (x, y));
+
+    (iter, usize::from(on_edge))
+}
+
+/// Whether the pixel at `(x, y)` of `image` is likely anti-aliasing, see pixelmatch's
+/// `antialiased`: it must sit between a darker and a brighter neighbour, one of which is part of
+/// a flat region in both images.
+fn anti_aliased(image: &RgbaImage, other: &RgbaImage, x: u32, y: u32) -> bool {
+    let center = luma(blend_white(image.pixel(x, y)));
+    let (neighbours, mut zeroes) = neighbours(image, x, y);
+
+    let (mut min, mut max) = (0.0, 0.0);
+    let (mut min_at, mut max_at) = ((0, 0), (0, 0));
+    for (nx, ny) in neighbours {
+        let delta = center - luma(blend_white(image.pixel(nx, ny)));
+        if delta == 0.0 {
+            zeroes += 1;
+            if zeroes > 2 {
+                return false;
+            }
+        } else if delta < min {
+            min = delta;
+            min_at = (nx, ny);
+        } else if delta > max {
+            max = delta;
+            max_at = (nx, ny);
+        }
+    }
+
+    if min == 0.0 || max == 0.0 {
+        return false;
+    }
+
+    let flat = |(px, py)| has_many_siblings(image, px, py) && has_many_siblings(other, px, py);
+    flat(min_at) || flat(max_at)
+}
+
+/// Whether at least 3 neighbours of `(x, y)` have exactly the same color.
+fn has_many_siblings(image: &RgbaImage, x: u32, y: u32) -> bool {
+    let color = image.pixel(x, y);
+    let (neighbours, mut zeroes) = neighbours(image, x, y);
+
+    for (nx, ny) in neighbours {
+        if image.pixel(nx, ny) == color {
+            zeroes += 1;
+            if zeroes > 2 {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+    fn image(width: u32, height: u32, color: impl Fn(u32, u32) -> [u8; 4]) -> RgbaImage {
+        let mut image = RgbaImage::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                image.set_pixel(x, y, color(x, y));
+            }
+        }
+        image
+    }
+
+    fn gradient() -> RgbaImage {
+        image(16, 16, |x, y| [(x * 16) as u8, (y * 16) as u8, 128, 255])
+    }
+
+    /// Black left half, white right half, column 8 is `edge`.
+    fn split(edge: [u8; 4]) -> RgbaImage {
+        image(16, 16, |x, _| match x {
+            0..=7 => BLACK,
+            8 => edge,
+            _ => WHITE,
+        })
+    }
+
+    #[test]
+    fn identical_images_have_no_difference() {
+        let diff = compare(&gradient(), &gradient(), &DiffOptions::default());
+
+        assert_eq!(diff.stats.total_pixels, 256);
+        assert_eq!(diff.stats.differing_pixels, 0);
+        assert_eq!(diff.stats.anti_aliased_pixels, 0);
+        assert_eq!(diff.stats.max_channel_delta, 0);
+    }
+
+    #[test]
+    fn single_pixel_change_is_counted() {
+        let expected = image(16, 16, |_, _| WHITE);
+        let mut actual = expected.clone();
+        actual.set_pixel(8, 8, BLACK);
+
+        let diff = compare(&expected, &actual, &DiffOptions::default());
+
+        assert_eq!(diff.stats.differing_pixels, 1);
+        assert_eq!(diff.stats.anti_aliased_pixels, 0);
+        assert_eq!(diff.stats.max_channel_delta, 255);
+        assert_eq!(diff.image.pixel(8, 8), DiffOptions::default().diff_color);
+    }
+
+    #[test]
+    fn channel_threshold_ignores_small_changes() {
+        let expected = image(16, 16, |_, _| WHITE);
+        let actual = image(16, 16, |_, _| [250, 250, 250, 255]);
+        let options = DiffOptions {
+            channel_threshold: 5,
+            ..DiffOptions::default()
+        };
+
+        assert_eq!(
+            compare(&expected, &actual, &options).stats.differing_pixels,
+            0
+        );
+    }
+
+    #[test]
+    fn anti_aliased_edge_is_ignored() {
+        let expected = split(WHITE);
+        let actual = split([128, 128, 128, 255]);
+
+        let diff = compare(&expected, &actual, &DiffOptions::default());
+        assert_eq!(diff.stats.differing_pixels, 0);
+        assert_eq!(diff.stats.anti_aliased_pixels, 16);
+
+        let options = DiffOptions {
+            detect_anti_aliasing: false,
+            ..DiffOptions::default()
+        };
+        let diff = compare(&expected, &actual, &options);
+        assert_eq!(diff.stats.differing_pixels, 16);
+        assert_eq!(diff.stats.anti_aliased_pixels, 0);
+    }
+
+    #[test]
+    fn ssim_of_identical_images_is_one() {
+        assert!((ssim(&gradient(), &gradient()) - 1.0).abs() < 1e-9);
+        assert!(ssim(&split(BLACK), &split(WHITE)) < 1.0);
+    }
+}
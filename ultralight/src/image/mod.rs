@@ -0,0 +1,188 @@
+pub mod diff;
+
+#[cfg(feature = "png")]
+use std::{fs::File, io, io::BufWriter, path::Path};
+
+use crate::surface::Surface;
+
+/// Owned, tightly packed RGBA8 image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `pixels` is not `width * height * 4` bytes long.
+    pub fn from_raw(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * 4,
+            "pixel buffer doesn't match the image size"
+        );
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Copy the content of a surface, converting BGRA to RGBA and dropping the row padding.
+    pub fn from_surface<S: Surface>(surface: &mut S) -> Self {
+        let (width, height) = (surface.width(), surface.height());
+        let (row_bytes, row_len) = (surface.row_bytes() as usize, width as usize * 4);
+
+        let guard = surface.pixels();
+        let pixels = guard
+            .pixels()
+            .chunks(row_bytes)
+            .take(height as usize)
+            .flat_map(|row| row[..row_len].chunks_exact(4))
+            .flat_map(|bgra| [bgra[2], bgra[1], bgra[0], bgra[3]])
+            .collect();
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    pub fn into_raw(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[i..i + 4].copy_from_slice(&rgba);
+    }
+
+    /// Copy out the `width` x `height` region at `(x, y)`, clamped to the image bounds.
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        let (x, y) = (x.min(self.width), y.min(self.height));
+        let (width, height) = (width.min(self.width - x), height.min(self.height - y));
+        let stride = self.width as usize * 4;
+
+        let pixels = self
+            .pixels
+            .chunks(stride)
+            .skip(y as usize)
+            .take(height as usize)
+            .flat_map(|row| &row[x as usize * 4..(x + width) as usize * 4])
+            .copied()
+            .collect();
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Surfaces hold premultiplied alpha, convert to straight alpha before encoding a
+    /// transparent image.
+    pub fn unpremultiply_alpha(&mut self) {
+        self.pixels.chunks_exact_mut(4).for_each(unpremultiply);
+    }
+}
+
+/// Convert a premultiplied pixel to straight alpha, alpha is last, the color channel order
+/// doesn't matter.
+pub(crate) fn unpremultiply(pixel: &mut [u8]) {
+    let alpha = u16::from(pixel[3]);
+    if alpha == 0 || alpha == 255 {
+        return;
+    }
+    for c in &mut pixel[..3] {
+        *c = ((u16::from(*c) * 255 + alpha / 2) / alpha).min(255) as u8;
+    }
+}
+
+#[cfg(feature = "png")]
+impl RgbaImage {
+    /// Decode a PNG file, any color type is expanded to 8-bit RGBA.
+    pub fn read_png(path: &Path) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(File::open(path)?);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(png_error)?;
+
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).map_err(png_error)?;
+        buf.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buf,
+            png::ColorType::Rgb => buf
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buf
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buf.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+            png::ColorType::Indexed => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "indexed PNG was not expanded",
+                ))
+            }
+        };
+
+        Ok(Self::from_raw(info.width, info.height, pixels))
+    }
+
+    pub fn write_png(&self, path: &Path) -> io::Result<()> {
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(File::create(path)?), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&self.pixels).map_err(png_error)?;
+        writer.finish().map_err(png_error)
+    }
+}
+
+#[cfg(feature = "png")]
+fn png_error<E: std::error::Error + Send + Sync + 'static>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
@@ -1,15 +1,29 @@
+mod affinity;
+mod clock;
 mod string;
 
+pub mod actor;
 pub mod buffer;
+pub mod clipboard;
 pub mod config;
+pub mod error;
 pub mod filesystem;
+pub mod image;
 pub mod logger;
+pub mod memory;
 pub mod platform;
+pub mod pool;
+pub mod recorder;
+pub mod render_loop;
 pub mod renderer;
 pub mod session;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
 pub mod surface;
 pub mod view;
 
+pub use error::Error;
+
 pub trait AsULRawPtr<P> {
     fn as_raw_ptr(&self) -> P;
 }
@@ -1,4 +1,8 @@
-use std::{mem::ManuallyDrop, ops::Deref, sync::RwLock};
+use std::{
+    mem::{self, ManuallyDrop},
+    ops::Deref,
+    sync::{Mutex, PoisonError, RwLock},
+};
 
 use ultralight_sys::*;
 
@@ -12,11 +16,23 @@ macro_rules! lock_fail {
 }
 
 static USER_LOGGER: RwLock<Option<UserLogger>> = RwLock::new(None);
+// messages are also collected here while set, see `capture`
+static CAPTURED: Mutex<Option<Vec<String>>> = Mutex::new(None);
 static LOGGER: ULLogger = ULLogger {
     log_message: Some(logger_callback),
 };
 
 unsafe extern "C" fn logger_callback(level: ULLogLevel, message: ULString) {
+    let message = ManuallyDrop::new(unsafe { UString::from_raw(message) });
+
+    if let Some(captured) = CAPTURED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_mut()
+    {
+        captured.push(message.to_owned());
+    }
+
     let guard = match USER_LOGGER.read() {
         Ok(guard) => guard,
         Err(_) => {
@@ -36,8 +52,6 @@ unsafe extern "C" fn logger_callback(level: ULLogLevel, message: ULString) {
         _ => unreachable!(),
     };
 
-    let message = ManuallyDrop::new(unsafe { UString::from_raw(message) });
-
     logger(level, message.deref());
 }
 
@@ -53,7 +67,7 @@ pub enum LogLevel {
     Info = ULLogLevel_kLogLevel_Info as i32,
 }
 
-pub fn set_platform_logger(logger: UserLogger) {
+pub(crate) fn set_platform_logger(logger: UserLogger) {
     match USER_LOGGER.write() {
         Ok(mut guard) => guard.insert(logger),
         Err(_) => {
@@ -65,3 +79,39 @@ pub fn set_platform_logger(logger: UserLogger) {
         ulPlatformSetLogger(LOGGER);
     }
 }
+
+/// Forget the user logger, when Ultralight logs somewhere else.
+pub(crate) fn clear_platform_logger() {
+    *USER_LOGGER.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Collect the messages logged while `f` runs, they are still passed to the user logger.
+///
+/// [`None`] if no user logger is set, the messages can't be intercepted then.
+pub(crate) fn capture(f: impl FnOnce()) -> Option<Vec<String>> {
+    if USER_LOGGER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_none()
+    {
+        f();
+        return None;
+    }
+
+    // stop capturing even if `f` panics
+    struct Capture;
+
+    impl Drop for Capture {
+        fn drop(&mut self) {
+            CAPTURED
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+        }
+    }
+
+    *CAPTURED.lock().unwrap_or_else(PoisonError::into_inner) = Some(Vec::new());
+    let _capture = Capture;
+    f();
+    mem::take(&mut *CAPTURED.lock().unwrap_or_else(PoisonError::into_inner))
+}
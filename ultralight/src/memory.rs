@@ -0,0 +1,128 @@
+//! Structured memory report of a [`Renderer`].
+
+use std::mem::ManuallyDrop;
+#[cfg(feature = "jsc-private")]
+use std::{ffi::CString, ptr::null_mut};
+
+use ultralight_sys::*;
+
+use crate::{logger, renderer::Renderer, string::UString};
+
+/// Statistics of the JavaScriptCore heap, shared by all views of a renderer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JsHeapStats {
+    /// Bytes used by live objects.
+    pub heap_size: usize,
+    /// Bytes reserved by the heap.
+    pub heap_capacity: usize,
+    /// Bytes reported by native objects owned by JS objects (eg. array buffers).
+    pub extra_memory_size: usize,
+    pub object_count: usize,
+    pub protected_object_count: usize,
+    pub global_object_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewMemory {
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+    /// Bytes of the pixel buffer, `0` for accelerated views.
+    pub surface_bytes: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryReport {
+    /// [`None`] if no view is alive, the heap is only reachable through a view.
+    ///
+    /// Read with a private JavaScriptCore API, always [`None`] without the `jsc-private`
+    /// feature.
+    pub js_heap: Option<JsHeapStats>,
+    pub views: Vec<ViewMemory>,
+    /// Lines logged by [`Renderer::log_memory_usage`] (WebCore caches, allocators, ...).
+    ///
+    /// Ultralight only reports these through the logger, [`None`] unless it is set with
+    /// [`Platform::set_logger`](crate::platform::Platform::set_logger).
+    pub renderer_log: Option<Vec<String>>,
+}
+
+impl MemoryReport {
+    pub fn surface_bytes(&self) -> usize {
+        self.views.iter().map(|v| v.surface_bytes).sum()
+    }
+
+    /// JS heap capacity, extra memory and surfaces, the part of the memory this report can
+    /// measure.
+    pub fn total_bytes(&self) -> usize {
+        let js = self
+            .js_heap
+            .as_ref()
+            .map_or(0, |js| js.heap_capacity + js.extra_memory_size);
+        js + self.surface_bytes()
+    }
+}
+
+pub(crate) fn report(renderer: &Renderer, views: &[ULView]) -> MemoryReport {
+    let views_memory = views.iter().map(|&view| view_memory(view)).collect();
+    let js_heap = views.first().and_then(|&view| js_heap_stats(view));
+    let renderer_log = logger::capture(|| renderer.log_memory_usage());
+
+    MemoryReport {
+        js_heap,
+        views: views_memory,
+        renderer_log,
+    }
+}
+
+fn view_memory(view: ULView) -> ViewMemory {
+    // owned by the view
+    let url = ManuallyDrop::new(unsafe { UString::from_raw(ulViewGetURL(view)) }).to_owned();
+    let surface = unsafe { ulViewGetSurface(view) };
+    let surface_bytes = match surface.is_null() {
+        true => 0,
+        false => unsafe { ulSurfaceGetSize(surface) },
+    };
+
+    ViewMemory {
+        url,
+        width: unsafe { ulViewGetWidth(view) },
+        height: unsafe { ulViewGetHeight(view) },
+        surface_bytes,
+    }
+}
+
+#[cfg(not(feature = "jsc-private"))]
+fn js_heap_stats(_view: ULView) -> Option<JsHeapStats> {
+    None
+}
+
+#[cfg(feature = "jsc-private")]
+fn js_heap_stats(view: ULView) -> Option<JsHeapStats> {
+    unsafe {
+        let ctx = ulViewLockJSContext(view);
+        let stats = JSGetMemoryUsageStatistics(ctx);
+        let get = |name: &str| -> usize {
+            let name = CString::new(name).unwrap();
+            let name = JSStringCreateWithUTF8CString(name.as_ptr());
+            let value = JSObjectGetProperty(ctx, stats, name, null_mut());
+            JSStringRelease(name);
+
+            match JSValueIsNumber(ctx, value) {
+                true => JSValueToNumber(ctx, value, null_mut()) as usize,
+                false => 0,
+            }
+        };
+
+        let heap = JsHeapStats {
+            heap_size: get("heapSize"),
+            heap_capacity: get("heapCapacity"),
+            extra_memory_size: get("extraMemorySize"),
+            object_count: get("objectCount"),
+            protected_object_count: get("protectedObjectCount"),
+            global_object_count: get("globalObjectCount"),
+        };
+        ulViewUnlockJSContext(view);
+
+        Some(heap)
+    }
+}
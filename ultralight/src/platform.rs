@@ -1,22 +1,354 @@
-use std::path::Path;
+//! Process-wide platform handlers and the [`Renderer`](crate::renderer::Renderer) lifecycle.
+//!
+//! Ultralight reads the logger, file system and font loader when a renderer is created, so they
+//! are registered through the [`Platform`] singleton and can only change while no renderer is
+//! alive. At most one renderer is alive at a time.
+//!
+//! Creating a renderer while another is alive fails with [`Error::AlreadyLoaded`]. Once it is
+//! dropped, a new one can be created, from any thread: the new renderer and its views are then
+//! bound to that thread.
+//!
+//! Tests creating renderers should hold [`Platform::lock`] for their whole duration, so they
+//! don't race for the renderer when run in parallel.
+//!
+//! [`Platform::builder`] registers everything and creates the renderer in one go.
+
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
 
 use ultralight_sys::*;
 
-use crate::{string::UString, AsULRawPtr};
+use crate::{
+    clipboard::{set_platform_clipboard, Clipboard},
+    config::Config,
+    error::Error,
+    filesystem::{set_platform_file_system, FileSystem},
+    logger::{clear_platform_logger, set_platform_logger, UserLogger},
+    renderer::Renderer,
+    string::UString,
+    surface::{set_platform_surface_definition, SurfaceDefinition},
+    AsULRawPtr,
+};
+
+/// Resources Ultralight loads through the file system, relative to the resource path prefix.
+const REQUIRED_RESOURCES: [&str; 2] = ["icudt67l.dat", "cacert.pem"];
+
+static PLATFORM: Platform = Platform {
+    state: Mutex::new(State {
+        renderer_alive: false,
+        font_loader: false,
+        logger: false,
+        file_system: false,
+    }),
+    serial: Mutex::new(()),
+};
+
+struct State {
+    renderer_alive: bool,
+    font_loader: bool,
+    logger: bool,
+    file_system: bool,
+}
+
+pub struct Platform {
+    state: Mutex<State>,
+    serial: Mutex<()>,
+}
+
+/// Serializes users of the [`Platform`], see [`Platform::lock`].
+pub struct PlatformGuard {
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Platform {
+    pub fn get() -> &'static Platform {
+        &PLATFORM
+    }
+
+    pub fn builder() -> PlatformBuilder {
+        PlatformBuilder::default()
+    }
+
+    /// Block until no other [`PlatformGuard`] is held.
+    ///
+    /// Only cooperative, nothing else in the crate takes this lock. A panic while holding the
+    /// guard doesn't poison it for the next holder.
+    pub fn lock(&'static self) -> PlatformGuard {
+        PlatformGuard {
+            _guard: self.serial.lock().unwrap_or_else(PoisonError::into_inner),
+        }
+    }
+
+    pub fn is_renderer_alive(&self) -> bool {
+        self.state().renderer_alive
+    }
+
+    pub fn has_font_loader(&self) -> bool {
+        self.state().font_loader
+    }
+
+    pub fn has_logger(&self) -> bool {
+        self.state().logger
+    }
+
+    pub fn has_file_system(&self) -> bool {
+        self.state().file_system
+    }
+
+    pub fn set_logger(&self, logger: UserLogger) -> Result<(), Error> {
+        let mut state = self.unlocked_state()?;
+        set_platform_logger(logger);
+        state.logger = true;
+        Ok(())
+    }
+
+    /// Log to a file with the logger bundled with AppCore.
+    pub fn set_default_logger(&self, path: &Path) -> Result<(), Error> {
+        let mut state = self.unlocked_state()?;
+        enable_default_logger(path);
+        clear_platform_logger();
+        state.logger = true;
+        Ok(())
+    }
+
+    pub fn set_file_system(&self, fs: Box<dyn FileSystem>) -> Result<(), Error> {
+        let mut state = self.unlocked_state()?;
+        set_platform_file_system(fs);
+        state.file_system = true;
+        Ok(())
+    }
+
+    /// Use the file system bundled with AppCore, rooted at `base_dir`.
+    pub fn set_default_file_system(&self, base_dir: &Path) -> Result<(), Error> {
+        let mut state = self.unlocked_state()?;
+        enable_platform_file_system(base_dir);
+        state.file_system = true;
+        Ok(())
+    }
+
+    pub fn set_clipboard(&self, clipboard: Box<dyn Clipboard>) -> Result<(), Error> {
+        let _state = self.unlocked_state()?;
+        set_platform_clipboard(clipboard);
+        Ok(())
+    }
+
+    pub fn set_surface_definition(
+        &self,
+        definition: Box<dyn SurfaceDefinition>,
+    ) -> Result<(), Error> {
+        let _state = self.unlocked_state()?;
+        set_platform_surface_definition(definition);
+        Ok(())
+    }
+
+    pub fn enable_font_loader(&self) -> Result<(), Error> {
+        let mut state = self.unlocked_state()?;
+        if !state.font_loader {
+            enable_platform_font_loader();
+            state.font_loader = true;
+        }
+        Ok(())
+    }
+}
+
+impl Platform {
+    /// Claim the renderer slot, released with [`Platform::release_renderer`].
+    pub(crate) fn acquire_renderer(&self) -> Result<(), Error> {
+        let mut state = self.state();
+        if state.renderer_alive {
+            return Err(Error::AlreadyLoaded);
+        }
+
+        state.renderer_alive = true;
+        Ok(())
+    }
+
+    pub(crate) fn release_renderer(&self) {
+        self.state().renderer_alive = false;
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn unlocked_state(&self) -> Result<MutexGuard<'_, State>, Error> {
+        let state = self.state();
+        match state.renderer_alive {
+            true => Err(Error::RendererAlive),
+            false => Ok(state),
+        }
+    }
+}
+
+enum LoggerChoice {
+    User(UserLogger),
+    File(PathBuf),
+}
+
+enum FileSystemChoice {
+    User(Box<dyn FileSystem>),
+    Dir(PathBuf),
+}
+
+/// Collects the platform handlers, validates them and creates the [`Renderer`].
+///
+/// NOTICE: GPU drivers are not bound by this crate, views always render on the CPU.
+pub struct PlatformBuilder {
+    logger: Option<LoggerChoice>,
+    file_system: Option<FileSystemChoice>,
+    font_loader: bool,
+    clipboard: Option<Box<dyn Clipboard>>,
+    surface_definition: Option<Box<dyn SurfaceDefinition>>,
+    config: Config,
+    resource_path_prefix: Option<String>,
+}
+
+impl Default for PlatformBuilder {
+    fn default() -> Self {
+        Self {
+            logger: None,
+            file_system: None,
+            font_loader: true,
+            clipboard: None,
+            surface_definition: None,
+            config: Config::default(),
+            resource_path_prefix: None,
+        }
+    }
+}
+
+impl PlatformBuilder {
+    pub fn logger(mut self, logger: UserLogger) -> Self {
+        self.logger = Some(LoggerChoice::User(logger));
+        self
+    }
+
+    /// Log to a file with the logger bundled with AppCore.
+    pub fn default_logger(mut self, path: impl Into<PathBuf>) -> Self {
+        self.logger = Some(LoggerChoice::File(path.into()));
+        self
+    }
+
+    pub fn file_system(mut self, fs: Box<dyn FileSystem>) -> Self {
+        self.file_system = Some(FileSystemChoice::User(fs));
+        self
+    }
+
+    /// Use the file system bundled with AppCore, rooted at `base_dir`.
+    pub fn default_file_system(mut self, base_dir: impl Into<PathBuf>) -> Self {
+        self.file_system = Some(FileSystemChoice::Dir(base_dir.into()));
+        self
+    }
+
+    /// Load fonts installed on the system.
+    ///
+    /// (Default = true)
+    pub fn font_loader(mut self, enabled: bool) -> Self {
+        self.font_loader = enabled;
+        self
+    }
+
+    pub fn clipboard(mut self, clipboard: Box<dyn Clipboard>) -> Self {
+        self.clipboard = Some(clipboard);
+        self
+    }
+
+    /// Render to custom surfaces instead of bitmaps.
+    pub fn surface_definition(mut self, definition: Box<dyn SurfaceDefinition>) -> Self {
+        self.surface_definition = Some(definition);
+        self
+    }
+
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Prefix of the bundled resources in the file system, overrides the one of the [`Config`].
+    ///
+    /// (Default = the [`Config`] one)
+    pub fn resource_path_prefix(mut self, prefix: &str) -> Self {
+        self.resource_path_prefix = Some(prefix.to_owned());
+        self
+    }
+
+    /// Check that the file system provides the ICU data and CA certificates, register the
+    /// handlers with [`Platform`] and create the [`Renderer`].
+    pub fn build(mut self) -> Result<Rc<Renderer>, Error> {
+        self.validate()?;
+
+        let platform = Platform::get();
+        match self.logger {
+            Some(LoggerChoice::User(logger)) => platform.set_logger(logger)?,
+            Some(LoggerChoice::File(path)) => platform.set_default_logger(&path)?,
+            None => {}
+        }
+        match self.file_system {
+            Some(FileSystemChoice::User(fs)) => platform.set_file_system(fs)?,
+            Some(FileSystemChoice::Dir(base_dir)) => platform.set_default_file_system(&base_dir)?,
+            // checked by validate
+            None => unreachable!(),
+        }
+        if self.font_loader {
+            platform.enable_font_loader()?;
+        }
+        if let Some(clipboard) = self.clipboard {
+            platform.set_clipboard(clipboard)?;
+        }
+        if let Some(definition) = self.surface_definition {
+            platform.set_surface_definition(definition)?;
+        }
+
+        if let Some(prefix) = &self.resource_path_prefix {
+            self.config.set_resource_path_prefix(prefix);
+        }
+        Renderer::try_new(&self.config)
+    }
+}
+
+impl PlatformBuilder {
+    fn validate(&self) -> Result<(), Error> {
+        if Platform::get().is_renderer_alive() {
+            return Err(Error::RendererAlive);
+        }
+
+        let fs = self.file_system.as_ref().ok_or(Error::MissingFileSystem)?;
+        let prefix = self
+            .resource_path_prefix
+            .as_deref()
+            .unwrap_or_else(|| self.config.resource_path_prefix());
+        for resource in REQUIRED_RESOURCES {
+            let path = Path::new(prefix).join(resource);
+            let exists = match fs {
+                FileSystemChoice::User(fs) => fs.file_exists(&path),
+                FileSystemChoice::Dir(base_dir) => base_dir.join(&path).is_file(),
+            };
+
+            if !exists {
+                return Err(Error::MissingResource(path));
+            }
+        }
+
+        Ok(())
+    }
+}
 
-pub fn enable_platform_font_loader() {
+pub(crate) fn enable_platform_font_loader() {
     unsafe {
         ulEnablePlatformFontLoader();
     }
 }
 
-pub fn enable_default_logger(path: &Path) {
+pub(crate) fn enable_default_logger(path: &Path) {
     let logger_path = UString::from(path.to_string_lossy());
     unsafe { ulEnableDefaultLogger(logger_path.as_raw_ptr()) };
 }
 
 /// NOTICE: the file system must provide the ICU data file, or it will make program exit.
-pub fn enable_platform_file_system(path: &Path) {
+pub(crate) fn enable_platform_file_system(path: &Path) {
     let base_dir = UString::from(path.to_string_lossy());
     unsafe { ulEnablePlatformFileSystem(base_dir.as_raw_ptr()) }
 }
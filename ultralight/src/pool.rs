@@ -0,0 +1,266 @@
+//! Reuse [`View`]s instead of creating one per render.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use ultralight_sys::ULView;
+
+use crate::{config::ViewConfig, error::Error, renderer::Renderer, view::View, AsULRawPtr};
+
+/// How long [`ViewPool`] waits for a returned view to load the blank page.
+const RESET_TIMEOUT: Duration = Duration::from_secs(1);
+/// Pause between renderer updates while waiting for the blank page.
+const RESET_POLL: Duration = Duration::from_millis(1);
+
+thread_local! {
+    // views that finished loading the blank page, see `ViewPool::release`
+    static RESET: RefCell<HashSet<ULView>> = RefCell::new(HashSet::new());
+}
+
+fn on_reset(caller: ULView, _frame_id: u64, is_main_frame: bool, _url: &str) {
+    if is_main_frame {
+        RESET.with(|reset| reset.borrow_mut().insert(caller));
+    }
+}
+
+type PoolKey = (u64, u32, u32);
+
+struct Entry<'a> {
+    view: View<'a>,
+    key: PoolKey,
+    uses: u32,
+}
+
+/// Pool of [`View`]s keyed by size and [`ViewConfig`].
+///
+/// Views are keyed by the identity of the [`ViewConfig`], so create configs once and reuse them.
+/// Callbacks set on pooled views must outlive the pool, like with [`View`] itself; they are
+/// cleared when a view is returned.
+pub struct ViewPool<'a> {
+    renderer: Rc<Renderer>,
+    idle: RefCell<HashMap<PoolKey, Vec<Entry<'a>>>>,
+    live: Cell<usize>,
+    checked_out: Cell<usize>,
+    max_views: usize,
+    max_uses: u32,
+    idle_purge_delay: Duration,
+    last_release: Cell<Option<Instant>>,
+}
+
+impl<'a> ViewPool<'a> {
+    pub fn new(renderer: Rc<Renderer>) -> Self {
+        Self {
+            renderer,
+            idle: RefCell::new(HashMap::new()),
+            live: Cell::new(0),
+            checked_out: Cell::new(0),
+            max_views: 8,
+            max_uses: 100,
+            idle_purge_delay: Duration::from_secs(30),
+            last_release: Cell::new(None),
+        }
+    }
+
+    /// Max number of views alive at once, idle or checked out.
+    ///
+    /// (Default = 8)
+    pub fn set_max_views(&mut self, max_views: usize) {
+        self.max_views = max_views;
+    }
+
+    /// Destroy a view after it has been handed out this many times, to bound memory growth.
+    ///
+    /// (Default = 100)
+    pub fn set_max_uses(&mut self, max_uses: u32) {
+        self.max_uses = max_uses;
+    }
+
+    /// How long the pool must be unused before [`ViewPool::maintain`] purges renderer memory.
+    ///
+    /// (Default = 30s)
+    pub fn set_idle_purge_delay(&mut self, delay: Duration) {
+        self.idle_purge_delay = delay;
+    }
+
+    /// Get a view, reusing an idle one when possible.
+    ///
+    /// Fails with [`Error::PoolExhausted`] if [`ViewPool::set_max_views`] views are checked out.
+    pub fn acquire(
+        &self,
+        config: &ViewConfig,
+        width: u32,
+        height: u32,
+    ) -> Result<PooledView<'_, 'a>, Error> {
+        let key = (config.id(), width, height);
+
+        let reused = self.take_idle(key);
+        let entry = match reused {
+            Some(entry) => entry,
+            None => {
+                if self.live.get() >= self.max_views && !self.evict_idle() {
+                    return Err(Error::PoolExhausted);
+                }
+
+                let session = self.renderer.clone().default_session();
+                let view = session.try_create_view(width, height, config)?;
+                self.live.set(self.live.get() + 1);
+
+                Entry { view, key, uses: 0 }
+            }
+        };
+
+        self.checked_out.set(self.checked_out.get() + 1);
+        self.last_release.set(None);
+
+        Ok(PooledView {
+            pool: self,
+            entry: Some(entry),
+        })
+    }
+
+    pub fn idle_views(&self) -> usize {
+        self.live.get() - self.checked_out.get()
+    }
+
+    pub fn checked_out_views(&self) -> usize {
+        self.checked_out.get()
+    }
+
+    /// Call periodically, purges renderer memory once the pool has been idle for
+    /// [`ViewPool::set_idle_purge_delay`].
+    pub fn maintain(&self) {
+        let idle_since = match self.last_release.get() {
+            Some(idle_since) if self.checked_out.get() == 0 => idle_since,
+            _ => return,
+        };
+
+        if idle_since.elapsed() >= self.idle_purge_delay {
+            self.renderer.purge_memory();
+            // only once per idle period
+            self.last_release.set(None);
+        }
+    }
+
+    /// Destroy all idle views.
+    pub fn clear(&self) {
+        let dropped: usize = self.idle.borrow_mut().drain().map(|(_, v)| v.len()).sum();
+        self.live.set(self.live.get() - dropped);
+    }
+}
+
+impl<'a> ViewPool<'a> {
+    /// Load a blank page and wait until it has finished, `false` on timeout.
+    fn reset(&self, view: &mut View<'a>) -> bool {
+        let raw = view.as_raw_ptr();
+        view.clear_callbacks();
+        view.set_finish_loading_callback(&on_reset);
+        view.load_html("");
+
+        let deadline = Instant::now() + RESET_TIMEOUT;
+        let finished = loop {
+            self.renderer.update();
+            if RESET.with(|reset| reset.borrow_mut().remove(&raw)) {
+                break true;
+            }
+            if Instant::now() >= deadline {
+                break false;
+            }
+            sleep(RESET_POLL);
+        };
+
+        view.clear_callbacks();
+        finished
+    }
+
+    /// Pop an idle view, dropping the key once it has none left.
+    fn take_idle(&self, key: PoolKey) -> Option<Entry<'a>> {
+        let mut idle = self.idle.borrow_mut();
+        let views = idle.get_mut(&key)?;
+        let entry = views.pop();
+        if views.is_empty() {
+            idle.remove(&key);
+        }
+        entry
+    }
+
+    /// Drop one idle view of any key to make room, return `false` if there was none.
+    fn evict_idle(&self) -> bool {
+        let mut idle = self.idle.borrow_mut();
+        let evicted = idle.values_mut().find_map(Vec::pop);
+
+        match evicted {
+            Some(_) => {
+                idle.retain(|_, v| !v.is_empty());
+                self.live.set(self.live.get() - 1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn release(&self, mut entry: Entry<'a>) {
+        self.checked_out.set(self.checked_out.get() - 1);
+        if self.checked_out.get() == 0 {
+            self.last_release.set(Some(Instant::now()));
+        }
+
+        entry.uses += 1;
+        if entry.uses >= self.max_uses {
+            self.live.set(self.live.get() - 1);
+            return;
+        }
+
+        // the next user must not see the previous page
+        if !self.reset(&mut entry.view) {
+            self.live.set(self.live.get() - 1);
+            return;
+        }
+
+        self.idle
+            .borrow_mut()
+            .entry(entry.key)
+            .or_default()
+            .push(entry);
+    }
+}
+
+/// A view checked out of a [`ViewPool`], returned to it on drop.
+pub struct PooledView<'p, 'a> {
+    pool: &'p ViewPool<'a>,
+    entry: Option<Entry<'a>>,
+}
+
+impl<'a> PooledView<'_, 'a> {
+    /// Number of times this view has been handed out before.
+    pub fn uses(&self) -> u32 {
+        self.entry.as_ref().map_or(0, |e| e.uses)
+    }
+}
+
+impl<'a> Deref for PooledView<'_, 'a> {
+    type Target = View<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entry.as_ref().unwrap().view
+    }
+}
+
+impl<'a> DerefMut for PooledView<'_, 'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.entry.as_mut().unwrap().view
+    }
+}
+
+impl Drop for PooledView<'_, '_> {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            self.pool.release(entry);
+        }
+    }
+}
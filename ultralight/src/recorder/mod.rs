@@ -0,0 +1,131 @@
+mod raw;
+mod y4m;
+
+use std::{
+    io,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+pub use raw::RawRgbaWriter;
+pub use y4m::Y4mWriter;
+
+use crate::{renderer::Renderer, surface::Surface, view::View};
+
+/// A single captured frame, pixels are BGRA as produced by the CPU renderer.
+pub struct Frame<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub row_bytes: u32,
+    pub pixels: &'a [u8],
+}
+
+impl Frame<'_> {
+    /// Iterate over rows with the alignment padding stripped.
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        let row_len = self.width as usize * 4;
+        self.pixels
+            .chunks(self.row_bytes as usize)
+            .take(self.height as usize)
+            .map(move |row| &row[..row_len])
+    }
+}
+
+/// Destination of recorded frames.
+pub trait FrameSink {
+    /// Called once by [`Recorder::new`] before any frame is written.
+    fn set_frame_rate(&mut self, _fps: u32) {}
+
+    fn write_frame(&mut self, frame: &Frame) -> io::Result<()>;
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<S: FrameSink + ?Sized> FrameSink for &mut S {
+    fn set_frame_rate(&mut self, fps: u32) {
+        (**self).set_frame_rate(fps)
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        (**self).write_frame(frame)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        (**self).finish()
+    }
+}
+
+/// Frame-stepping driver, pumps the [`Renderer`] at a fixed frame rate and feeds the surface of
+/// a [`View`] into a [`FrameSink`].
+pub struct Recorder<S: FrameSink> {
+    sink: S,
+    frame_interval: Duration,
+}
+
+impl<S: FrameSink> Recorder<S> {
+    /// Record at `fps` frames per second, the frame rate is passed on to the sink.
+    pub fn new(mut sink: S, fps: u32) -> Self {
+        assert!(fps > 0, "frame rate must be positive");
+        sink.set_frame_rate(fps);
+
+        Self {
+            sink,
+            frame_interval: Duration::from_secs(1) / fps,
+        }
+    }
+
+    pub fn frame_interval(&self) -> Duration {
+        self.frame_interval
+    }
+
+    /// Capture the current surface of `view` without stepping the renderer.
+    pub fn capture(&mut self, view: &mut View) -> io::Result<()> {
+        let mut surface = view.surface();
+        let (width, height, row_bytes) = (surface.width(), surface.height(), surface.row_bytes());
+        let pixels = surface.pixels();
+
+        self.sink.write_frame(&Frame {
+            width,
+            height,
+            row_bytes,
+            pixels: pixels.pixels(),
+        })
+    }
+
+    /// Step the renderer `frames` times, capturing one frame after each step.
+    ///
+    /// With [`Renderer::enable_virtual_clock`] every step advances the virtual clock by one
+    /// frame interval and never sleeps, otherwise steps are paced by the wall clock.
+    pub fn record(&mut self, renderer: &Renderer, view: &mut View, frames: u32) -> io::Result<()> {
+        let start = Instant::now();
+        let virtual_clock = renderer.is_virtual_clock_enabled();
+
+        for i in 1..=frames {
+            if virtual_clock {
+                renderer.advance(self.frame_interval);
+            } else {
+                renderer.update();
+            }
+            renderer.render_view(view);
+            self.capture(view)?;
+
+            if virtual_clock {
+                continue;
+            }
+
+            let deadline = start + self.frame_interval * i;
+            if let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                sleep(remaining);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<S> {
+        self.sink.finish()?;
+        Ok(self.sink)
+    }
+}
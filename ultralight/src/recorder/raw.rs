@@ -0,0 +1,47 @@
+use std::io::{self, Write};
+
+use crate::{
+    image::unpremultiply,
+    recorder::{Frame, FrameSink},
+};
+
+/// Writes frames as tightly packed RGBA with straight alpha, consumable by
+/// `ffmpeg -f rawvideo -pix_fmt rgba -s WxH -r FPS -i -`.
+pub struct RawRgbaWriter<W: Write> {
+    writer: W,
+    row: Vec<u8>,
+}
+
+impl<W: Write> RawRgbaWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            row: Vec::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> FrameSink for RawRgbaWriter<W> {
+    fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        for row in frame.rows() {
+            self.row.clear();
+            self.row.extend(
+                row.chunks_exact(4)
+                    .flat_map(|bgra| [bgra[2], bgra[1], bgra[0], bgra[3]]),
+            );
+            // the renderer produces premultiplied alpha
+            self.row.chunks_exact_mut(4).for_each(unpremultiply);
+            self.writer.write_all(&self.row)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,174 @@
+use std::io::{self, Write};
+
+use crate::recorder::{Frame, FrameSink};
+
+/// Writes frames as a YUV4MPEG2 stream (4:2:0, BT.601 limited range), consumable by
+/// `ffmpeg -f yuv4mpegpipe -i -`.
+///
+/// The stream header is written with the first frame, all following frames must have the same
+/// dimensions. The frame rate is the one of the [`Recorder`](crate::recorder::Recorder).
+pub struct Y4mWriter<W: Write> {
+    writer: W,
+    fps: u32,
+    size: Option<(u32, u32)>,
+    planes: [Vec<u8>; 3],
+}
+
+impl<W: Write> Y4mWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            // replaced by the recorder
+            fps: 30,
+            size: None,
+            planes: Default::default(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn convert(&mut self, frame: &Frame) {
+        let (width, height) = (frame.width as usize, frame.height as usize);
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        let [y_plane, u_plane, v_plane] = &mut self.planes;
+
+        y_plane.clear();
+        for row in frame.rows() {
+            y_plane.extend(row.chunks_exact(4).map(|bgra| {
+                let [b, g, r] = [bgra[0], bgra[1], bgra[2]].map(i32::from);
+                (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8
+            }));
+        }
+
+        let rows: Vec<&[u8]> = frame.rows().collect();
+        u_plane.clear();
+        v_plane.clear();
+        for cy in 0..chroma_height {
+            for cx in 0..chroma_width {
+                // average the 2x2 block, edges of odd sized frames are partial blocks
+                let (mut sum, mut count) = ([0i32; 3], 0);
+                for row in &rows[cy * 2..(cy * 2 + 2).min(height)] {
+                    for bgra in row[cx * 8..(cx * 8 + 8).min(width * 4)].chunks_exact(4) {
+                        sum[0] += i32::from(bgra[0]);
+                        sum[1] += i32::from(bgra[1]);
+                        sum[2] += i32::from(bgra[2]);
+                        count += 1;
+                    }
+                }
+                let [b, g, r] = sum.map(|c| c / count);
+
+                u_plane.push((((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8);
+                v_plane.push((((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8);
+            }
+        }
+    }
+}
+
+impl<W: Write> FrameSink for Y4mWriter<W> {
+    fn set_frame_rate(&mut self, fps: u32) {
+        self.fps = fps;
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        match self.size {
+            None => {
+                writeln!(
+                    self.writer,
+                    "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C420jpeg",
+                    frame.width, frame.height, self.fps
+                )?;
+                self.size = Some((frame.width, frame.height));
+            }
+            Some(size) if size != (frame.width, frame.height) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "frame size changed during Y4M recording",
+                ));
+            }
+            Some(_) => {}
+        }
+
+        self.convert(frame);
+
+        self.writer.write_all(b"FRAME\n")?;
+        for plane in &self.planes {
+            self.writer.write_all(plane)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(width: u32, height: u32, row_bytes: u32, bgra: [u8; 4]) -> Vec<u8> {
+        let mut pixels = vec![0; (row_bytes * height) as usize];
+        for row in pixels.chunks_mut(row_bytes as usize) {
+            for pixel in row[..width as usize * 4].chunks_exact_mut(4) {
+                pixel.copy_from_slice(&bgra);
+            }
+        }
+        pixels
+    }
+
+    fn convert(width: u32, height: u32, row_bytes: u32, bgra: [u8; 4]) -> [Vec<u8>; 3] {
+        let pixels = frame(width, height, row_bytes, bgra);
+        let mut writer = Y4mWriter::new(Vec::new());
+        writer.convert(&Frame {
+            width,
+            height,
+            row_bytes,
+            pixels: &pixels,
+        });
+        writer.planes
+    }
+
+    #[test]
+    fn convert_limited_range() {
+        let [y, u, v] = convert(2, 2, 8, [255, 255, 255, 255]);
+        assert_eq!((y, u, v), (vec![235; 4], vec![128], vec![128]));
+
+        let [y, u, v] = convert(2, 2, 8, [0, 0, 0, 255]);
+        assert_eq!((y, u, v), (vec![16; 4], vec![128], vec![128]));
+    }
+
+    #[test]
+    fn convert_reads_bgra() {
+        let [y, u, v] = convert(2, 2, 8, [0, 0, 255, 255]);
+        assert_eq!((y, u, v), (vec![82; 4], vec![90], vec![240]));
+    }
+
+    #[test]
+    fn convert_odd_size_and_row_padding() {
+        let [y, u, v] = convert(3, 3, 16, [255, 255, 255, 255]);
+        assert_eq!(y, vec![235; 9]);
+        assert_eq!(u, vec![128; 4]);
+        assert_eq!(v, vec![128; 4]);
+    }
+
+    #[test]
+    fn header_uses_frame_rate() {
+        let pixels = frame(2, 2, 8, [0, 0, 0, 255]);
+        let mut writer = Y4mWriter::new(Vec::new());
+        writer.set_frame_rate(25);
+        writer
+            .write_frame(&Frame {
+                width: 2,
+                height: 2,
+                row_bytes: 8,
+                pixels: &pixels,
+            })
+            .unwrap();
+
+        let output = writer.into_inner();
+        assert!(output.starts_with(b"YUV4MPEG2 W2 H2 F25:1 "));
+    }
+}
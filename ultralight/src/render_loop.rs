@@ -0,0 +1,152 @@
+//! Drive [`Renderer::update`] and [`Renderer::render`] at a fixed rate.
+
+use std::{
+    rc::Rc,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use crate::renderer::Renderer;
+
+/// Lowest rate accepted by [`RenderLoop::set_tick_rate`], one update every 1000s.
+pub const MIN_TICK_RATE: f64 = 0.001;
+
+/// Information about the frame passed to the hooks.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameInfo {
+    /// Number of the frame, starting at 0.
+    pub frame: u64,
+    /// Time since the first frame.
+    pub elapsed: Duration,
+    /// Whether [`Renderer::render`] ran, always `false` in [`RenderLoop::on_before_update`].
+    pub rendered: bool,
+}
+
+type Hook<'a> = Box<dyn FnMut(&FrameInfo) + 'a>;
+
+/// Calls [`Renderer::update`] every tick, [`Renderer::render`] only when a view needs paint,
+/// and sleeps until the next tick in between.
+pub struct RenderLoop<'a> {
+    renderer: Rc<Renderer>,
+    tick: Duration,
+    before_update: Vec<Hook<'a>>,
+    after_render: Vec<Hook<'a>>,
+    frame: u64,
+    start: Option<Instant>,
+}
+
+impl<'a> RenderLoop<'a> {
+    pub fn new(renderer: Rc<Renderer>) -> Self {
+        Self {
+            renderer,
+            tick: Duration::from_secs(1) / 60,
+            before_update: Vec::new(),
+            after_render: Vec::new(),
+            frame: 0,
+            start: None,
+        }
+    }
+
+    /// Time between two updates.
+    ///
+    /// (Default = 1/60s)
+    pub fn set_tick(&mut self, tick: Duration) {
+        self.tick = tick;
+    }
+
+    /// Same as [`RenderLoop::set_tick`] with the tick given as updates per second.
+    ///
+    /// `fps` is clamped to at least [`MIN_TICK_RATE`], zero, negative and NaN rates included. An
+    /// infinite rate runs frames back to back.
+    pub fn set_tick_rate(&mut self, fps: f64) {
+        self.tick = tick_from_rate(fps);
+    }
+
+    /// Called at the start of every frame, before [`Renderer::update`].
+    pub fn on_before_update(&mut self, hook: impl FnMut(&FrameInfo) + 'a) {
+        self.before_update.push(Box::new(hook));
+    }
+
+    /// Called at the end of every frame, after [`Renderer::render`] if it ran.
+    pub fn on_after_render(&mut self, hook: impl FnMut(&FrameInfo) + 'a) {
+        self.after_render.push(Box::new(hook));
+    }
+
+    /// Run a single frame without sleeping.
+    pub fn step(&mut self) -> FrameInfo {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let mut info = FrameInfo {
+            frame: self.frame,
+            elapsed: start.elapsed(),
+            rendered: false,
+        };
+
+        for hook in &mut self.before_update {
+            hook(&info);
+        }
+
+        self.renderer.update();
+        if self.renderer.needs_paint() {
+            self.renderer.render();
+            info.rendered = true;
+        }
+
+        for hook in &mut self.after_render {
+            hook(&info);
+        }
+
+        self.frame += 1;
+        info
+    }
+
+    /// Run frames at the tick rate until `condition` returns `false`, it is checked after every
+    /// frame.
+    ///
+    /// Frames that are late don't pile up, the loop skips to the next tick instead.
+    pub fn run_while(&mut self, mut condition: impl FnMut(&FrameInfo) -> bool) {
+        let mut deadline = Instant::now();
+
+        loop {
+            let info = self.step();
+            if !condition(&info) {
+                return;
+            }
+
+            deadline += self.tick;
+            let now = Instant::now();
+            if deadline > now {
+                sleep(deadline - now);
+            } else {
+                deadline = now;
+            }
+        }
+    }
+
+    /// Run frames at the tick rate for `duration`.
+    pub fn run_for(&mut self, duration: Duration) {
+        let end = Instant::now() + duration;
+        self.run_while(|_| Instant::now() < end);
+    }
+}
+
+fn tick_from_rate(fps: f64) -> Duration {
+    // `max` also replaces NaN
+    Duration::from_secs_f64(1.0 / fps.max(MIN_TICK_RATE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick_rate_is_clamped() {
+        assert_eq!(tick_from_rate(4.0), Duration::from_millis(250));
+        assert_eq!(tick_from_rate(f64::INFINITY), Duration::ZERO);
+
+        let slowest = Duration::from_secs(1000);
+        assert_eq!(tick_from_rate(0.0), slowest);
+        assert_eq!(tick_from_rate(-60.0), slowest);
+        assert_eq!(tick_from_rate(f64::NAN), slowest);
+        assert_eq!(tick_from_rate(f64::NEG_INFINITY), slowest);
+    }
+}
@@ -1,32 +1,70 @@
-use std::{
-    rc::Rc,
-    sync::atomic::{AtomicBool, Ordering},
-};
+use std::{cell::RefCell, rc::Rc, time::Duration};
 
 use ultralight_sys::*;
 
-use crate::{config::Config, session::Session, AsULRawPtr};
-
-static LOADED: AtomicBool = AtomicBool::new(false);
+use crate::{
+    affinity::{assert_owner_thread, clear_owner_thread, set_owner_thread},
+    clock::VirtualClock,
+    config::Config,
+    error::Error,
+    memory::{self, MemoryReport},
+    platform::Platform,
+    session::Session,
+    view::View,
+    AsULRawPtr,
+};
 
-pub struct Renderer(ULRenderer);
+pub struct Renderer {
+    renderer: ULRenderer,
+    clock: VirtualClock,
+    views: RefCell<Vec<ULView>>,
+}
 
 impl Renderer {
+    /// Panic if a [`Renderer`] is already alive, see [`Renderer::try_new`].
+    ///
+    /// Platform handlers must be registered with [`Platform`] before.
     pub fn new(config: &Config) -> Rc<Self> {
-        if LOADED.swap(true, Ordering::SeqCst) {
-            panic!("Ultralight has already been loaded. You can only load it once.");
+        Self::try_new(config).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_new(config: &Config) -> Result<Rc<Self>, Error> {
+        let platform = Platform::get();
+        platform.acquire_renderer()?;
+
+        let renderer = unsafe { ulCreateRenderer(config.as_raw_ptr()) };
+        if renderer.is_null() {
+            platform.release_renderer();
+            return Err(Error::CreateFailed("renderer"));
         }
 
-        Rc::new(Renderer(unsafe { ulCreateRenderer(config.as_raw_ptr()) }))
+        set_owner_thread();
+
+        Ok(Rc::new(Renderer {
+            renderer,
+            clock: VirtualClock::new(),
+            views: RefCell::new(Vec::new()),
+        }))
     }
 
     pub fn create_session(self: Rc<Self>, is_persistent: bool, name: &str) -> Rc<Session> {
+        self.try_create_session(is_persistent, name)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_create_session(
+        self: Rc<Self>,
+        is_persistent: bool,
+        name: &str,
+    ) -> Result<Rc<Session>, Error> {
+        assert_owner_thread();
         Session::new(self, is_persistent, name)
     }
 
     /// Get the default [`Session`] for the given [`Renderer`].
     /// The default session should not be destroyed. So we add some magic in [`Session::drop`].
     pub fn default_session(self: Rc<Self>) -> Rc<Session> {
+        assert_owner_thread();
         let renderer_ptr = self.as_raw_ptr();
         let session = unsafe { Session::from_raw(self, ulDefaultSession(renderer_ptr)) };
 
@@ -39,31 +77,123 @@ impl Renderer {
     }
 
     pub fn update(&self) {
-        unsafe { ulUpdate(self.0) }
+        assert_owner_thread();
+        unsafe { ulUpdate(self.renderer) }
     }
 
     pub fn render(&self) {
-        unsafe { ulRender(self.0) }
+        assert_owner_thread();
+        unsafe { ulRender(self.renderer) }
+    }
+
+    /// Render only `view`, other views keep their needs-paint state for a later render.
+    pub fn render_view(&self, view: &View) {
+        assert_owner_thread();
+        let target = view.as_raw_ptr();
+        let others: Vec<(ULView, bool)> = self
+            .views
+            .borrow()
+            .iter()
+            .copied()
+            .filter(|&v| v != target)
+            .map(|v| (v, unsafe { ulViewGetNeedsPaint(v) }))
+            .collect();
+
+        for &(v, _) in &others {
+            unsafe { ulViewSetNeedsPaint(v, false) };
+        }
+        unsafe { ulRender(self.renderer) }
+        for &(v, needs_paint) in &others {
+            unsafe { ulViewSetNeedsPaint(v, needs_paint) };
+        }
     }
 
     pub fn purge_memory(&self) {
-        unsafe { ulPurgeMemory(self.0) }
+        assert_owner_thread();
+        unsafe { ulPurgeMemory(self.renderer) }
     }
 
     pub fn log_memory_usage(&self) {
-        unsafe { ulLogMemoryUsage(self.0) }
+        assert_owner_thread();
+        unsafe { ulLogMemoryUsage(self.renderer) }
+    }
+
+    /// Gather JS heap statistics, surface sizes of all views and the renderer memory log.
+    pub fn memory_report(&self) -> MemoryReport {
+        assert_owner_thread();
+        memory::report(self, &self.views.borrow())
+    }
+
+    /// [`Renderer::purge_memory`] if [`MemoryReport::total_bytes`] exceeds `threshold`, returns
+    /// whether it did.
+    pub fn purge_memory_above(&self, threshold: usize) -> bool {
+        let exceeded = self.memory_report().total_bytes() > threshold;
+        if exceeded {
+            self.purge_memory();
+        }
+        exceeded
+    }
+
+    /// Switch to deterministic mode, page time only moves forward with [`Renderer::advance`].
+    ///
+    /// `Date`, `performance.now`, timers, `requestAnimationFrame` and CSS animations of views
+    /// created after this call are driven by a virtual clock starting at `epoch` (since the Unix
+    /// epoch), so the same sequence of advances renders the same frames.
+    pub fn enable_virtual_clock(&self, epoch: Duration) {
+        self.clock.enable(epoch);
+    }
+
+    pub fn is_virtual_clock_enabled(&self) -> bool {
+        self.clock.is_enabled()
+    }
+
+    /// Time elapsed on the virtual clock.
+    pub fn virtual_time(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    /// Advance the virtual clock by `duration`, fire due timers and animation frames, then
+    /// [`Renderer::update`].
+    pub fn advance(&self, duration: Duration) {
+        self.clock.advance(duration);
+        self.update();
+    }
+}
+
+impl Renderer {
+    pub(crate) fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+
+    /// Whether any view has pending paint.
+    pub(crate) fn needs_paint(&self) -> bool {
+        self.views
+            .borrow()
+            .iter()
+            .any(|&view| unsafe { ulViewGetNeedsPaint(view) })
+    }
+
+    pub(crate) fn register_view(&self, view: ULView) {
+        self.views.borrow_mut().push(view);
+    }
+
+    pub(crate) fn unregister_view(&self, view: ULView) {
+        self.views.borrow_mut().retain(|&v| v != view);
     }
 }
 
 impl AsULRawPtr<ULRenderer> for Renderer {
     fn as_raw_ptr(&self) -> ULRenderer {
-        self.0
+        self.renderer
     }
 }
 
 impl Drop for Renderer {
     fn drop(&mut self) {
-        LOADED.store(false, Ordering::SeqCst);
-        unsafe { ulDestroyRenderer(self.0) }
+        assert_owner_thread();
+
+        unsafe { ulDestroyRenderer(self.renderer) }
+        clear_owner_thread();
+        Platform::get().release_renderer();
     }
 }
@@ -1,8 +1,11 @@
-use std::rc::Rc;
+use std::{mem::ManuallyDrop, rc::Rc};
 
 use ultralight_sys::*;
 
-use crate::{config::ViewConfig, renderer::Renderer, string::UString, view::View, AsULRawPtr};
+use crate::{
+    affinity::assert_owner_thread, config::ViewConfig, error::Error, renderer::Renderer,
+    string::UString, view::View, AsULRawPtr,
+};
 
 pub struct Session {
     session: ULSession,
@@ -11,37 +14,64 @@ pub struct Session {
 
 impl Session {
     pub fn is_persistent(&self) -> bool {
+        assert_owner_thread();
         unsafe { ulSessionIsPersistent(self.session) }
     }
 
     pub fn name(&self) -> String {
-        unsafe { UString::from_raw(ulSessionGetName(self.session)) }.to_owned()
+        assert_owner_thread();
+        // owned by the session
+        ManuallyDrop::new(unsafe { UString::from_raw(ulSessionGetName(self.session)) }).to_owned()
     }
 
     pub fn id(&self) -> u64 {
+        assert_owner_thread();
         unsafe { ulSessionGetId(self.session) }
     }
 
     pub fn disk_path(&self) -> String {
-        unsafe { UString::from_raw(ulSessionGetDiskPath(self.session)) }.to_owned()
+        assert_owner_thread();
+        // owned by the session
+        ManuallyDrop::new(unsafe { UString::from_raw(ulSessionGetDiskPath(self.session)) })
+            .to_owned()
     }
 
-    pub fn create_view(
+    /// The [`ViewConfig`] is only read during creation, the [`View`] doesn't borrow it.
+    pub fn create_view<'a>(
         self: Rc<Session>,
         width: u32,
         height: u32,
         view_config: &ViewConfig,
-    ) -> View {
+    ) -> View<'a> {
+        self.try_create_view(width, height, view_config)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_create_view<'a>(
+        self: Rc<Session>,
+        width: u32,
+        height: u32,
+        view_config: &ViewConfig,
+    ) -> Result<View<'a>, Error> {
         View::new(self, width, height, view_config)
     }
 }
 
 impl Session {
-    pub(crate) fn new(renderer: Rc<Renderer>, is_persistent: bool, name: &str) -> Rc<Session> {
+    pub(crate) fn new(
+        renderer: Rc<Renderer>,
+        is_persistent: bool,
+        name: &str,
+    ) -> Result<Rc<Session>, Error> {
+        assert_owner_thread();
         let s = UString::from(name);
         let session =
             unsafe { ulCreateSession(renderer.as_raw_ptr(), is_persistent, s.as_raw_ptr()) };
-        Rc::new(Session { session, renderer })
+        if session.is_null() {
+            return Err(Error::CreateFailed("session"));
+        }
+
+        Ok(Rc::new(Session { session, renderer }))
     }
 
     pub(crate) unsafe fn from_raw(renderer: Rc<Renderer>, session: ULSession) -> Session {
@@ -61,6 +91,7 @@ impl AsULRawPtr<ULSession> for Session {
 
 impl Drop for Session {
     fn drop(&mut self) {
+        assert_owner_thread();
         let default_session = unsafe { ulDefaultSession(self.renderer.as_raw_ptr()) };
 
         // default session should not be destroyed.
@@ -0,0 +1,282 @@
+//! Snapshot testing of rendered pages against reference PNGs.
+//!
+//! References live in a directory as `<name>.png`. On mismatch `<name>.actual.png` and
+//! `<name>.diff.png` are written next to it. Run with `ULTRALIGHT_BLESS=1` to (re)write the
+//! references from the current output.
+//!
+//! NOTICE: only one [`Renderer`] can be alive at a time and it is not [`Send`], hold
+//! [`Platform::lock`](crate::platform::Platform::lock) in every snapshot test, or run them with
+//! `--test-threads=1`.
+
+use std::{
+    cell::Cell,
+    env,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::PathBuf,
+    rc::Rc,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::ViewConfig,
+    image::{
+        diff::{self, DiffOptions},
+        RgbaImage,
+    },
+    renderer::Renderer,
+    view::View,
+};
+
+pub const BLESS_ENV: &str = "ULTRALIGHT_BLESS";
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    LoadFailed {
+        url: String,
+        description: String,
+        error_code: i32,
+    },
+    Timeout,
+    MissingReference(PathBuf),
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    Mismatch {
+        differing_pixels: usize,
+        max_differing_pixels: usize,
+        diff_path: PathBuf,
+    },
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "snapshot I/O error: {e}"),
+            Self::LoadFailed {
+                url,
+                description,
+                error_code,
+            } => write!(f, "failed to load {url}: {description} ({error_code})"),
+            Self::Timeout => write!(f, "page didn't finish loading in time"),
+            Self::MissingReference(path) => write!(
+                f,
+                "reference {} doesn't exist, run with {BLESS_ENV}=1 to create it",
+                path.display()
+            ),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "size mismatch, expected {}x{}, got {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            Self::Mismatch {
+                differing_pixels,
+                max_differing_pixels,
+                diff_path,
+            } => write!(
+                f,
+                "{differing_pixels} pixels differ (max {max_differing_pixels}), see {}",
+                diff_path.display()
+            ),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+pub struct Snapshot {
+    renderer: Rc<Renderer>,
+    reference_dir: PathBuf,
+    view_config: ViewConfig,
+    width: u32,
+    height: u32,
+    diff_options: DiffOptions,
+    max_differing_pixels: usize,
+    timeout: Duration,
+}
+
+impl Snapshot {
+    pub fn new(renderer: Rc<Renderer>, reference_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            renderer,
+            reference_dir: reference_dir.into(),
+            view_config: ViewConfig::default(),
+            width: 800,
+            height: 600,
+            diff_options: DiffOptions::default(),
+            max_differing_pixels: 0,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// (Default = 800x600)
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn set_view_config(&mut self, view_config: ViewConfig) {
+        self.view_config = view_config;
+    }
+
+    /// Max per-channel difference for two pixels to be considered equal.
+    ///
+    /// (Default = 0)
+    pub fn set_tolerance(&mut self, tolerance: u8) {
+        self.diff_options.channel_threshold = tolerance;
+    }
+
+    /// See [`DiffOptions`], anti-aliasing detection is enabled by default.
+    pub fn set_diff_options(&mut self, diff_options: DiffOptions) {
+        self.diff_options = diff_options;
+    }
+
+    /// Number of differing pixels allowed before the comparison fails.
+    ///
+    /// (Default = 0)
+    pub fn set_max_differing_pixels(&mut self, max_differing_pixels: usize) {
+        self.max_differing_pixels = max_differing_pixels;
+    }
+
+    /// (Default = 10s)
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn render_html(&self, html: &str) -> Result<RgbaImage, SnapshotError> {
+        self.render(|view| view.load_html(html))
+    }
+
+    pub fn render_url(&self, url: &str) -> Result<RgbaImage, SnapshotError> {
+        self.render(|view| view.load_url(url))
+    }
+
+    /// Compare `actual` with the reference `name`, or replace the reference when blessing.
+    pub fn check(&self, name: &str, actual: &RgbaImage) -> Result<(), SnapshotError> {
+        let reference = self.reference_dir.join(format!("{name}.png"));
+
+        if env::var_os(BLESS_ENV).is_some_and(|v| !v.is_empty() && v != "0") {
+            fs::create_dir_all(&self.reference_dir)?;
+            return Ok(actual.write_png(&reference)?);
+        }
+
+        if !reference.exists() {
+            return Err(SnapshotError::MissingReference(reference));
+        }
+        let expected = RgbaImage::read_png(&reference)?;
+
+        let (expected_size, actual_size) = (
+            (expected.width(), expected.height()),
+            (actual.width(), actual.height()),
+        );
+        if expected_size != actual_size {
+            actual.write_png(&reference.with_extension("actual.png"))?;
+            return Err(SnapshotError::SizeMismatch {
+                expected: expected_size,
+                actual: actual_size,
+            });
+        }
+
+        let diff = diff::compare(&expected, actual, &self.diff_options);
+        if diff.stats.differing_pixels <= self.max_differing_pixels {
+            return Ok(());
+        }
+
+        let diff_path = reference.with_extension("diff.png");
+        actual.write_png(&reference.with_extension("actual.png"))?;
+        diff.image.write_png(&diff_path)?;
+
+        Err(SnapshotError::Mismatch {
+            differing_pixels: diff.stats.differing_pixels,
+            max_differing_pixels: self.max_differing_pixels,
+            diff_path,
+        })
+    }
+
+    pub fn check_html(&self, name: &str, html: &str) -> Result<(), SnapshotError> {
+        self.check(name, &self.render_html(html)?)
+    }
+
+    pub fn check_url(&self, name: &str, url: &str) -> Result<(), SnapshotError> {
+        self.check(name, &self.render_url(url)?)
+    }
+
+    /// Like [`Snapshot::check_html`], but panics with the error, for use in `#[test]`.
+    #[track_caller]
+    pub fn assert_html(&self, name: &str, html: &str) {
+        if let Err(e) = self.check_html(name, html) {
+            panic!("snapshot `{name}` failed: {e}");
+        }
+    }
+
+    /// Like [`Snapshot::check_url`], but panics with the error, for use in `#[test]`.
+    #[track_caller]
+    pub fn assert_url(&self, name: &str, url: &str) {
+        if let Err(e) = self.check_url(name, url) {
+            panic!("snapshot `{name}` failed: {e}");
+        }
+    }
+}
+
+impl Snapshot {
+    fn render(&self, load: impl FnOnce(&mut View)) -> Result<RgbaImage, SnapshotError> {
+        let done: Cell<Option<Result<(), SnapshotError>>> = Cell::new(None);
+        let on_finish = |_, _, is_main_frame, _: &str| {
+            if is_main_frame {
+                done.set(Some(Ok(())));
+            }
+        };
+        let on_fail = |_, _, is_main_frame, url: &str, description: &str, _: &str, error_code| {
+            if is_main_frame {
+                done.set(Some(Err(SnapshotError::LoadFailed {
+                    url: url.to_owned(),
+                    description: description.to_owned(),
+                    error_code,
+                })));
+            }
+        };
+
+        let session = self.renderer.clone().default_session();
+        let mut view = session.create_view(self.width, self.height, &self.view_config);
+        view.set_finish_loading_callback(&on_finish);
+        view.set_fail_loading_callback(&on_fail);
+
+        load(&mut view);
+
+        let deadline = Instant::now() + self.timeout;
+        let result = loop {
+            self.renderer.update();
+            if let Some(result) = done.take() {
+                break result;
+            }
+            if Instant::now() > deadline {
+                break Err(SnapshotError::Timeout);
+            }
+            sleep(Duration::from_millis(1));
+        };
+        result?;
+
+        // one more pass to flush layout and paint after load
+        self.renderer.update();
+        self.renderer.render();
+
+        Ok(RgbaImage::from_surface(&mut view.surface()))
+    }
+}
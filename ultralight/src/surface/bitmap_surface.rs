@@ -6,6 +6,8 @@ use std::{
 use ultralight_sys::*;
 
 use crate::{
+    affinity::assert_owner_thread,
+    error::Error,
     surface::{generic_surface::GenericSurface, PixelGuard, Surface},
     AsULRawPtr,
 };
@@ -16,14 +18,19 @@ pub struct BitmapSurface<'a> {
 }
 
 impl<'a> BitmapSurface<'a> {
-    pub fn write_png(&self, path: &Path) {
-        let path = CString::new(path.to_string_lossy().as_bytes()).unwrap();
-        unsafe {
-            ulBitmapWritePNG(self.bitmap, path.as_ptr());
+    pub fn write_png(&self, path: &Path) -> Result<(), Error> {
+        assert_owner_thread();
+        let c_path = CString::new(path.to_string_lossy().as_bytes())
+            .map_err(|_| Error::InvalidPath(path.to_owned()))?;
+
+        match unsafe { ulBitmapWritePNG(self.bitmap, c_path.as_ptr()) } {
+            true => Ok(()),
+            false => Err(Error::WritePng(path.to_owned())),
         }
     }
 
     pub fn swap_red_blue(&mut self) {
+        assert_owner_thread();
         unsafe {
             ulBitmapSwapRedBlueChannels(self.bitmap);
         }
@@ -32,6 +39,7 @@ impl<'a> BitmapSurface<'a> {
 
 impl<'a> From<GenericSurface<'a>> for BitmapSurface<'a> {
     fn from(surface: GenericSurface<'a>) -> Self {
+        assert_owner_thread();
         let bitmap = unsafe { ulBitmapSurfaceGetBitmap(surface.as_raw_ptr()) };
         Self {
             bitmap,
@@ -42,18 +50,22 @@ impl<'a> From<GenericSurface<'a>> for BitmapSurface<'a> {
 
 impl Surface for BitmapSurface<'_> {
     fn width(&self) -> u32 {
+        assert_owner_thread();
         unsafe { ulBitmapGetWidth(self.bitmap) }
     }
 
     fn height(&self) -> u32 {
+        assert_owner_thread();
         unsafe { ulBitmapGetHeight(self.bitmap) }
     }
 
     fn row_bytes(&self) -> u32 {
+        assert_owner_thread();
         unsafe { ulBitmapGetRowBytes(self.bitmap) }
     }
 
     fn size(&self) -> usize {
+        assert_owner_thread();
         unsafe { ulBitmapGetSize(self.bitmap) }
     }
 
@@ -62,10 +74,12 @@ impl Surface for BitmapSurface<'_> {
     }
 
     unsafe fn lock_pixels(&self) -> *mut c_void {
+        assert_owner_thread();
         ulBitmapLockPixels(self.bitmap)
     }
 
     unsafe fn unlock_pixels(&self) {
+        assert_owner_thread();
         ulBitmapUnlockPixels(self.bitmap)
     }
 }
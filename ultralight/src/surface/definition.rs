@@ -0,0 +1,185 @@
+use std::{
+    ffi::c_void,
+    panic::{self, AssertUnwindSafe},
+    sync::{PoisonError, RwLock},
+};
+
+use ultralight_sys::*;
+
+static USER_SURFACE_DEFINITION: RwLock<Option<Box<dyn SurfaceDefinition>>> = RwLock::new(None);
+static SURFACE_DEFINITION: ULSurfaceDefinition = ULSurfaceDefinition {
+    create: Some(create_callback),
+    destroy: Some(destroy_callback),
+    get_width: Some(get_width_callback),
+    get_height: Some(get_height_callback),
+    get_row_bytes: Some(get_row_bytes_callback),
+    get_size: Some(get_size_callback),
+    lock_pixels: Some(lock_pixels_callback),
+    unlock_pixels: Some(unlock_pixels_callback),
+    resize: Some(resize_callback),
+};
+
+// user data of the surfaces created by `create_callback`
+struct SurfaceData {
+    surface: Box<dyn CustomSurface>,
+    // last size requested by Ultralight, to replace a panicking surface
+    width: u32,
+    height: u32,
+}
+
+/// Run `f` with the surface. A panic must not unwind into Ultralight, the surface is replaced by
+/// a [`BufferSurface`] and `f` runs again on it.
+unsafe fn with_surface<T>(user_data: *mut c_void, f: impl Fn(&mut dyn CustomSurface) -> T) -> T {
+    let data = &mut *(user_data as *mut SurfaceData);
+
+    match panic::catch_unwind(AssertUnwindSafe(|| f(&mut *data.surface))) {
+        Ok(value) => value,
+        Err(_) => {
+            data.surface = Box::new(BufferSurface::new(data.width, data.height));
+            f(&mut *data.surface)
+        }
+    }
+}
+
+unsafe extern "C" fn create_callback(width: u32, height: u32) -> *mut c_void {
+    let surface = panic::catch_unwind(|| {
+        let guard = USER_SURFACE_DEFINITION
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        guard
+            .as_ref()
+            .map(|definition| definition.create(width, height))
+    });
+
+    let surface = match surface {
+        Ok(Some(surface)) => surface,
+        Ok(None) | Err(_) => Box::new(BufferSurface::new(width, height)),
+    };
+
+    Box::into_raw(Box::new(SurfaceData {
+        surface,
+        width,
+        height,
+    }))
+    .cast()
+}
+
+unsafe extern "C" fn destroy_callback(user_data: *mut c_void) {
+    let data = Box::from_raw(user_data as *mut SurfaceData);
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(data)));
+}
+
+unsafe extern "C" fn get_width_callback(user_data: *mut c_void) -> u32 {
+    with_surface(user_data, |surface| surface.width())
+}
+
+unsafe extern "C" fn get_height_callback(user_data: *mut c_void) -> u32 {
+    with_surface(user_data, |surface| surface.height())
+}
+
+unsafe extern "C" fn get_row_bytes_callback(user_data: *mut c_void) -> u32 {
+    with_surface(user_data, |surface| surface.row_bytes())
+}
+
+unsafe extern "C" fn get_size_callback(user_data: *mut c_void) -> usize {
+    with_surface(user_data, |surface| surface.size())
+}
+
+unsafe extern "C" fn lock_pixels_callback(user_data: *mut c_void) -> *mut c_void {
+    with_surface(user_data, |surface| {
+        surface.lock_pixels().as_mut_ptr().cast()
+    })
+}
+
+unsafe extern "C" fn unlock_pixels_callback(user_data: *mut c_void) {
+    with_surface(user_data, |surface| surface.unlock_pixels())
+}
+
+unsafe extern "C" fn resize_callback(user_data: *mut c_void, width: u32, height: u32) {
+    let data = &mut *(user_data as *mut SurfaceData);
+    data.width = width;
+    data.height = height;
+    with_surface(user_data, |surface| surface.resize(width, height))
+}
+
+/// Plain pixel buffer, used when the [`SurfaceDefinition`] is missing or panics.
+struct BufferSurface {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl BufferSurface {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+}
+
+impl CustomSurface for BufferSurface {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn row_bytes(&self) -> u32 {
+        self.width * 4
+    }
+
+    fn size(&self) -> usize {
+        self.pixels.len()
+    }
+
+    fn lock_pixels(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    fn unlock_pixels(&mut self) {}
+
+    fn resize(&mut self, width: u32, height: u32) {
+        *self = Self::new(width, height);
+    }
+}
+
+/// Factory for the surfaces views render to, replaces the default bitmap surfaces.
+///
+/// NOTICE: views then no longer have a [`BitmapSurface`](crate::surface::BitmapSurface).
+///
+/// Panics in [`SurfaceDefinition::create`] and [`CustomSurface`] methods are caught, the surface
+/// is replaced by a plain pixel buffer with the content discarded.
+pub trait SurfaceDefinition: Send + Sync {
+    fn create(&self, width: u32, height: u32) -> Box<dyn CustomSurface>;
+}
+
+/// A surface created by a [`SurfaceDefinition`], only used on the renderer thread.
+///
+/// Pixels are BGRA, premultiplied alpha.
+pub trait CustomSurface {
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+    fn row_bytes(&self) -> u32;
+    fn size(&self) -> usize;
+
+    /// The returned pixels must stay at the same address until [`CustomSurface::unlock_pixels`].
+    fn lock_pixels(&mut self) -> &mut [u8];
+    fn unlock_pixels(&mut self);
+
+    /// Resize the pixel buffer, the previous content can be discarded.
+    fn resize(&mut self, width: u32, height: u32);
+}
+
+pub(crate) fn set_platform_surface_definition(definition: Box<dyn SurfaceDefinition>) {
+    *USER_SURFACE_DEFINITION
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(definition);
+
+    unsafe {
+        ulPlatformSetSurfaceDefinition(SURFACE_DEFINITION);
+    }
+}
@@ -3,7 +3,7 @@ use std::{cell::Cell, ffi::c_void, marker::PhantomData};
 use ultralight_sys::*;
 
 use super::PixelGuard;
-use crate::{surface::Surface, AsULRawPtr};
+use crate::{affinity::assert_owner_thread, surface::Surface, AsULRawPtr};
 
 pub struct GenericSurface<'a> {
     surface: ULSurface,
@@ -34,18 +34,22 @@ impl AsULRawPtr<ULSurface> for GenericSurface<'_> {
 
 impl Surface for GenericSurface<'_> {
     fn width(&self) -> u32 {
+        assert_owner_thread();
         unsafe { ulSurfaceGetWidth(self.surface) }
     }
 
     fn height(&self) -> u32 {
+        assert_owner_thread();
         unsafe { ulSurfaceGetHeight(self.surface) }
     }
 
     fn row_bytes(&self) -> u32 {
+        assert_owner_thread();
         unsafe { ulSurfaceGetRowBytes(self.surface) }
     }
 
     fn size(&self) -> usize {
+        assert_owner_thread();
         unsafe { ulSurfaceGetSize(self.surface) }
     }
 
@@ -54,10 +58,12 @@ impl Surface for GenericSurface<'_> {
     }
 
     unsafe fn lock_pixels(&self) -> *mut c_void {
+        assert_owner_thread();
         ulSurfaceLockPixels(self.surface)
     }
 
     unsafe fn unlock_pixels(&self) {
+        assert_owner_thread();
         ulSurfaceUnlockPixels(self.surface)
     }
 }
@@ -1,9 +1,12 @@
 mod bitmap_surface;
+mod definition;
 mod generic_surface;
 
 use std::{ffi::c_void, slice};
 
 pub use bitmap_surface::BitmapSurface;
+pub(crate) use definition::set_platform_surface_definition;
+pub use definition::{CustomSurface, SurfaceDefinition};
 pub use generic_surface::GenericSurface;
 
 pub trait Surface: Sized {
@@ -3,62 +3,87 @@ use std::{cell::Cell, ffi, marker::PhantomData, mem::ManuallyDrop, ptr::null_mut
 use ultralight_sys::*;
 
 use crate::{
-    config::ViewConfig, session::Session, string::UString, surface::GenericSurface, AsULRawPtr,
+    affinity::assert_owner_thread, config::ViewConfig, error::Error, session::Session,
+    string::UString, surface::GenericSurface, AsULRawPtr,
 };
 
 pub struct View<'a> {
     view: ULView,
-    _session: Rc<Session>,
+    session: Rc<Session>,
     callback_lifetime: PhantomData<Cell<&'a ()>>,
 }
 
 impl<'a> View<'a> {
     pub fn url(&self) -> String {
-        let url = unsafe { UString::from_raw(ulViewGetURL(self.view)) };
+        assert_owner_thread();
+        // owned by the view
+        let url = ManuallyDrop::new(unsafe { UString::from_raw(ulViewGetURL(self.view)) });
         url.to_owned()
     }
 
     pub fn title(&self) -> String {
-        let title = unsafe { UString::from_raw(ulViewGetTitle(self.view)) };
+        assert_owner_thread();
+        let title = ManuallyDrop::new(unsafe { UString::from_raw(ulViewGetTitle(self.view)) });
         title.to_owned()
     }
 
     pub fn width(&self) -> u32 {
+        assert_owner_thread();
         unsafe { ulViewGetWidth(self.view) }
     }
 
     pub fn height(&self) -> u32 {
+        assert_owner_thread();
         unsafe { ulViewGetHeight(self.view) }
     }
 
     pub fn device_scale(&self) -> f64 {
+        assert_owner_thread();
         unsafe { ulViewGetDeviceScale(self.view) }
     }
 
     pub fn set_device_scale(&mut self, scale: f64) {
+        assert_owner_thread();
         unsafe {
             ulViewSetDeviceScale(self.view, scale);
         }
     }
 
     pub fn is_accelerated(&self) -> bool {
+        assert_owner_thread();
         unsafe { ulViewIsAccelerated(self.view) }
     }
 
     pub fn is_transparent(&self) -> bool {
+        assert_owner_thread();
         unsafe { ulViewIsTransparent(self.view) }
     }
 
     pub fn is_loading(&self) -> bool {
+        assert_owner_thread();
         unsafe { ulViewIsLoading(self.view) }
     }
 
+    /// Whether the view will be painted by the next [`Renderer::render`](crate::renderer::Renderer::render).
+    pub fn needs_paint(&self) -> bool {
+        assert_owner_thread();
+        unsafe { ulViewGetNeedsPaint(self.view) }
+    }
+
+    /// Force (or skip) a repaint of the whole view on the next render.
+    pub fn set_needs_paint(&mut self, needs_paint: bool) {
+        assert_owner_thread();
+        unsafe { ulViewSetNeedsPaint(self.view, needs_paint) }
+    }
+
     pub fn surface(&mut self) -> GenericSurface {
+        assert_owner_thread();
         let surface = unsafe { ulViewGetSurface(self.view) };
         unsafe { GenericSurface::from_raw(surface) }
     }
 
     pub fn load_html(&mut self, html_string: &str) {
+        assert_owner_thread();
         let html = UString::from(html_string);
         unsafe {
             ulViewLoadHTML(self.view, html.as_raw_ptr());
@@ -66,6 +91,7 @@ impl<'a> View<'a> {
     }
 
     pub fn load_url(&mut self, url: &str) {
+        assert_owner_thread();
         let url = UString::from(url);
         unsafe {
             ulViewLoadURL(self.view, url.as_raw_ptr());
@@ -73,6 +99,7 @@ impl<'a> View<'a> {
     }
 
     pub fn resize(&mut self, width: u32, height: u32) {
+        assert_owner_thread();
         unsafe {
             ulViewResize(self.view, width, height);
         }
@@ -81,6 +108,7 @@ impl<'a> View<'a> {
     /// NOTICE: [`Ok`] doesn't mean no exception, return with no [`Err`] and "throw '';" is indistinguishable
     /// with simple [`ulViewEvaluateScript`].
     pub fn evaluate_script(&mut self, script: &str) -> Result<String, String> {
+        assert_owner_thread();
         let script = UString::from(script);
         let mut exception: ULString = null_mut();
 
@@ -98,11 +126,22 @@ impl<'a> View<'a> {
         }
     }
 
+    /// Unset the begin/finish/fail loading callbacks.
+    pub fn clear_callbacks(&mut self) {
+        assert_owner_thread();
+        unsafe {
+            ulViewSetBeginLoadingCallback(self.view, None, null_mut());
+            ulViewSetFinishLoadingCallback(self.view, None, null_mut());
+            ulViewSetFailLoadingCallback(self.view, None, null_mut());
+        }
+    }
+
     // TODO: impl all callbacks with macro
     pub fn set_begin_loading_callback<F>(&mut self, callback: &'a F)
     where
         F: Fn(ULView, u64, bool, &str),
     {
+        assert_owner_thread();
         unsafe extern "C" fn wrapper<F>(
             user_data: *mut ffi::c_void,
             caller: ULView,
@@ -130,6 +169,7 @@ impl<'a> View<'a> {
     where
         F: Fn(ULView, u64, bool, &str),
     {
+        assert_owner_thread();
         unsafe extern "C" fn wrapper<F>(
             user_data: *mut ffi::c_void,
             caller: ULView,
@@ -157,6 +197,7 @@ impl<'a> View<'a> {
     where
         F: Fn(ULView, u64, bool, &str, &str, &str, i32),
     {
+        assert_owner_thread();
         unsafe extern "C" fn wrapper<F>(
             user_data: *mut ffi::c_void,
             caller: ULView,
@@ -200,7 +241,8 @@ impl View<'_> {
         width: u32,
         height: u32,
         view_config: &ViewConfig,
-    ) -> Self {
+    ) -> Result<Self, Error> {
+        assert_owner_thread();
         let view = unsafe {
             ulCreateView(
                 session.renderer().as_raw_ptr(),
@@ -210,11 +252,21 @@ impl View<'_> {
                 session.as_raw_ptr(),
             )
         };
-        Self {
+        if view.is_null() {
+            return Err(Error::CreateFailed("view"));
+        }
+
+        let renderer = session.renderer();
+        renderer.register_view(view);
+        if renderer.is_virtual_clock_enabled() {
+            renderer.clock().register(view);
+        }
+
+        Ok(Self {
             view,
-            _session: session,
+            session,
             callback_lifetime: PhantomData,
-        }
+        })
     }
 }
 
@@ -226,6 +278,11 @@ impl AsULRawPtr<ULView> for View<'_> {
 
 impl Drop for View<'_> {
     fn drop(&mut self) {
+        assert_owner_thread();
+        let renderer = self.session.renderer();
+        renderer.unregister_view(self.view);
+        renderer.clock().unregister(self.view);
+
         unsafe {
             ulDestroyView(self.view);
         }
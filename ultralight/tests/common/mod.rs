@@ -0,0 +1,27 @@
+//! Shared setup of the tests that need a live renderer, they link against the SDK and embed its
+//! resources from `ULTRALIGHT_SDK_PATH`.
+
+#![allow(dead_code)]
+
+use std::rc::Rc;
+
+use ultralight::{
+    embed_sdk_resources,
+    filesystem::EmbeddedFileSystem,
+    logger::LogLevel,
+    platform::{Platform, PlatformBuilder},
+    renderer::Renderer,
+};
+
+/// Builder serving the SDK resources, hold [`Platform::lock`] while the renderer is alive.
+pub fn builder() -> PlatformBuilder {
+    Platform::builder()
+        .file_system(Box::new(EmbeddedFileSystem::new(embed_sdk_resources!())))
+        .font_loader(true)
+}
+
+pub fn renderer() -> Rc<Renderer> {
+    builder().build().unwrap()
+}
+
+pub fn quiet_logger(_: LogLevel, _: &str) {}
@@ -0,0 +1,34 @@
+mod common;
+
+use ultralight::{config::ViewConfig, platform::Platform};
+
+#[test]
+fn memory_report_keeps_view_url() {
+    let _guard = Platform::get().lock();
+    let renderer = common::renderer();
+    let mut view = renderer
+        .clone()
+        .default_session()
+        .create_view(64, 64, &ViewConfig::default());
+    view.load_html("<p>memory</p>");
+    renderer.update();
+
+    let first = renderer.memory_report();
+    let second = renderer.memory_report();
+
+    assert_eq!(first.views.len(), 1);
+    assert_eq!(first.views, second.views);
+    assert_eq!(view.url(), first.views[0].url);
+}
+
+#[test]
+fn memory_report_captures_renderer_log() {
+    let _guard = Platform::get().lock();
+    let renderer = common::builder()
+        .logger(common::quiet_logger)
+        .build()
+        .unwrap();
+
+    let report = renderer.memory_report();
+    assert!(report.renderer_log.is_some());
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use std::thread;
+
+use ultralight::{config::Config, error::Error, platform::Platform, renderer::Renderer};
+
+#[test]
+fn only_one_renderer_at_a_time() {
+    let _guard = Platform::get().lock();
+    let renderer = common::renderer();
+
+    assert!(Platform::get().is_renderer_alive());
+    assert_eq!(
+        Renderer::try_new(&Config::default()).err(),
+        Some(Error::AlreadyLoaded)
+    );
+
+    drop(renderer);
+    assert!(!Platform::get().is_renderer_alive());
+}
+
+#[test]
+fn renderer_recreated_after_drop() {
+    let _guard = Platform::get().lock();
+
+    for _ in 0..2 {
+        let renderer = common::renderer();
+        renderer.update();
+        renderer.render();
+    }
+
+    // on another thread than the first one
+    thread::spawn(|| {
+        let renderer = common::renderer();
+        renderer.update();
+        renderer.render();
+    })
+    .join()
+    .unwrap();
+
+    assert!(!Platform::get().is_renderer_alive());
+}